@@ -0,0 +1,116 @@
+use nalgebra::Vector3;
+
+use crate::{airflow::Airflow, cockpit::Cockpit, wing::Wing};
+
+/// drag coefficient of a bare wheel and tyre side on
+const BARE_WHEEL_DRAG: f64 = 0.5;
+/// with a spat over it
+const FAIRED_WHEEL_DRAG: f64 = 0.25;
+
+/// anything on the plane the air pushes on
+pub trait AeroComponent: Send + Sync {
+    /// forces in body coordinates and moments about the cg
+    fn loads(&self, airflow: &Airflow) -> (Vector3<f64>, Vector3<f64>);
+
+    /// move any control surfaces, most things don't have any
+    fn update_controls(&mut self, _controls: &Cockpit) {}
+
+    /// let anything driven by a motor, like flaps, move on by dt seconds
+    fn move_actuators(&mut self, _dt: f64) {}
+
+    /// for things that only understand wings, like the vortex lattice
+    fn as_wing(&self) -> Option<&Wing> {
+        None
+    }
+}
+
+/// something blunt that only makes drag, like a strut, an aerial or an underwing tank
+pub struct BluffBody {
+    /// relative to the datum, m
+    pub position: Vector3<f64>,
+    /// m^2
+    pub frontal_area: f64,
+    pub drag_coefficient: f64,
+}
+
+impl BluffBody {
+    pub fn new(position: Vector3<f64>, frontal_area: f64, drag_coefficient: f64) -> BluffBody {
+        BluffBody {
+            position,
+            frontal_area,
+            drag_coefficient,
+        }
+    }
+}
+
+impl AeroComponent for BluffBody {
+    /// straight back along the local airflow, the same from every direction
+    fn loads(&self, airflow: &Airflow) -> (Vector3<f64>, Vector3<f64>) {
+        let velocity = airflow.velocity_at(&self.position);
+        let speed = velocity.norm();
+        let dynamic_pressure = 0.5 * airflow.atmosphere.density * speed.powi(2);
+        let forces = -velocity * dynamic_pressure * self.frontal_area * self.drag_coefficient
+            / speed.max(f64::EPSILON);
+        let moments = (self.position - airflow.cg).cross(&forces);
+        (forces, moments)
+    }
+}
+
+/// gear that stays down, the wheels hanging in the air make a lot of drag on a small plane
+pub struct FixedGear {
+    drag: BluffBody,
+}
+
+impl FixedGear {
+    /// a bare wheel and leg at the axle, diameter and width of the tyre
+    pub fn wheel(axle: Vector3<f64>, diameter: f64, width: f64) -> FixedGear {
+        FixedGear {
+            drag: BluffBody::new(axle, diameter * width, BARE_WHEEL_DRAG),
+        }
+    }
+
+    /// a spat round the wheel roughly halves its drag
+    pub fn with_fairing(mut self) -> FixedGear {
+        self.drag.drag_coefficient = FAIRED_WHEEL_DRAG;
+        self
+    }
+}
+
+impl AeroComponent for FixedGear {
+    fn loads(&self, airflow: &Airflow) -> (Vector3<f64>, Vector3<f64>) {
+        self.drag.loads(airflow)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+
+    use crate::{
+        aero::{AeroComponent, BluffBody, FixedGear},
+        airflow::Airflow,
+    };
+
+    #[test]
+    fn bluff_body_drags_along_the_airflow() {
+        let under_test = BluffBody::new(Vector3::new(0.0, 0.0, 1.0), 0.5, 1.0);
+        let airflow = Airflow::uniform(Vector3::new(30.0, 0.0, 0.0));
+        let (forces, moments) = under_test.loads(&airflow);
+        let expected = 0.5 * airflow.atmosphere.density * 900.0 * 0.5;
+        assert!((forces + Vector3::new(expected, 0.0, 0.0)).norm() < 1e-9);
+        // hanging below the cg, the drag pitches the nose down
+        assert!(moments.y < 0.0);
+        let (forces, _) = under_test.loads(&Airflow::uniform(Vector3::zeros()));
+        assert_eq!(forces, Vector3::zeros());
+    }
+
+    #[test]
+    fn fairings_halve_the_drag() {
+        let airflow = Airflow::uniform(Vector3::new(50.0, 0.0, 0.0));
+        let bare = FixedGear::wheel(Vector3::new(-0.5, 1.2, 0.6), 0.4, 0.15);
+        let faired = FixedGear::wheel(Vector3::new(-0.5, 1.2, 0.6), 0.4, 0.15).with_fairing();
+        let ratio = faired.loads(&airflow).0.x / bare.loads(&airflow).0.x;
+        assert!((ratio - 0.5).abs() < 1e-12);
+        assert!(faired.as_wing().is_none());
+    }
+}
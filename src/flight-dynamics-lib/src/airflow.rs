@@ -0,0 +1,112 @@
+use nalgebra::{UnitQuaternion, Vector3};
+
+use crate::atmosphere::Atmosphere;
+
+/// how the plane is moving through the air, everything in body coordinates
+pub struct Airflow {
+    /// velocity of the cg through the air, m/s
+    pub velocity: Vector3<f64>,
+    /// p, q, r in rad/s
+    pub angular_velocity: Vector3<f64>,
+    /// relative to the datum
+    pub cg: Vector3<f64>,
+    /// angle the main wing has bent the air down by, only felt by surfaces behind it, rad
+    pub downwash: f64,
+    /// cg above the ground, m, infinite when there's no ground to worry about
+    pub height: f64,
+    /// body to world, for working out which way is down
+    pub attitude: UnitQuaternion<f64>,
+    /// the air the plane is flying through, sea level unless told otherwise
+    pub atmosphere: Atmosphere,
+}
+
+impl Airflow {
+    pub fn new(
+        velocity: Vector3<f64>,
+        angular_velocity: Vector3<f64>,
+        cg: Vector3<f64>,
+    ) -> Airflow {
+        Airflow {
+            velocity,
+            angular_velocity,
+            cg,
+            downwash: 0.0,
+            height: f64::INFINITY,
+            attitude: UnitQuaternion::identity(),
+            atmosphere: Atmosphere::sea_level(),
+        }
+    }
+
+    pub fn with_downwash(mut self, downwash: f64) -> Airflow {
+        self.downwash = downwash;
+        self
+    }
+
+    pub fn with_ground(mut self, height: f64, attitude: UnitQuaternion<f64>) -> Airflow {
+        self.height = height;
+        self.attitude = attitude;
+        self
+    }
+
+    pub fn with_atmosphere(mut self, atmosphere: Atmosphere) -> Airflow {
+        self.atmosphere = atmosphere;
+        self
+    }
+
+    /// the same air everywhere, nothing rotating
+    pub fn uniform(velocity: Vector3<f64>) -> Airflow {
+        Airflow::new(velocity, Vector3::zeros(), Vector3::zeros())
+    }
+
+    /// velocity through the air of a point on the plane, relative to the datum
+    pub fn velocity_at(&self, point: &Vector3<f64>) -> Vector3<f64> {
+        self.velocity + self.angular_velocity.cross(&(point - self.cg))
+    }
+
+    /// height above the ground of a point on the plane, relative to the datum
+    pub fn height_at(&self, point: &Vector3<f64>) -> f64 {
+        self.height - (self.attitude * (point - self.cg)).z
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::{UnitQuaternion, Vector3};
+
+    use crate::airflow::Airflow;
+
+    #[test]
+    fn uniform_is_the_same_everywhere() {
+        let under_test = Airflow::uniform(Vector3::new(40.0, 1.0, 2.0));
+        assert_eq!(
+            under_test.velocity_at(&Vector3::new(-5.0, 3.0, 1.0)),
+            Vector3::new(40.0, 1.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn rotation_adds_on_about_the_cg() {
+        let under_test = Airflow::new(
+            Vector3::new(40.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.5, 0.0),
+            Vector3::new(0.5, 0.0, 0.0),
+        );
+        // pitching up, the tail drops through the air
+        let tail = under_test.velocity_at(&Vector3::new(-4.5, 0.0, 0.0));
+        assert!((tail - Vector3::new(40.0, 0.0, 2.5)).norm() < 1e-12);
+        // rolling right, the right wing drops and the left rises
+        let right = under_test.velocity_at(&Vector3::new(0.5, 2.0, 0.0));
+        assert!((right - Vector3::new(40.0, 0.0, 2.0)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn heights_follow_the_attitude() {
+        let level = Airflow::uniform(Vector3::new(40.0, 0.0, 0.0))
+            .with_ground(2.0, UnitQuaternion::identity());
+        assert_eq!(level.height_at(&Vector3::new(-5.0, 3.0, 0.5)), 1.5);
+        // nose up, the tail sits lower
+        let nose_up = level.with_ground(2.0, UnitQuaternion::from_euler_angles(0.0, 0.1, 0.0));
+        assert!(nose_up.height_at(&Vector3::new(-5.0, 0.0, 0.0)) < 2.0);
+        assert!(Airflow::uniform(Vector3::zeros()).height_at(&Vector3::zeros()) > 1e9);
+    }
+}
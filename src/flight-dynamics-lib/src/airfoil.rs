@@ -0,0 +1,420 @@
+use std::{fmt, fs, io, path::Path};
+
+#[derive(Debug)]
+pub enum AirfoilError {
+    Io(io::Error),
+    /// line numbers start at 1
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// a polar needs at least two points to interpolate between
+    TooFewPoints,
+    /// a NaN or infinity somewhere in the polar at this reynolds number
+    NotFinite {
+        reynolds: f64,
+    },
+    /// there's no alpha every polar covers
+    NoOverlap,
+    /// zero or negative, there's no log to interpolate in
+    BadReynolds {
+        reynolds: f64,
+    },
+}
+
+impl fmt::Display for AirfoilError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AirfoilError::Io(error) => write!(f, "couldn't read polar: {error}"),
+            AirfoilError::Parse { line, message } => write!(f, "line {line}: {message}"),
+            AirfoilError::TooFewPoints => write!(f, "polar has fewer than two points"),
+            AirfoilError::NotFinite { reynolds } => {
+                write!(
+                    f,
+                    "polar at reynolds number {reynolds} isn't all finite numbers"
+                )
+            }
+            AirfoilError::NoOverlap => write!(f, "the polars don't share any alpha"),
+            AirfoilError::BadReynolds { reynolds } => {
+                write!(f, "reynolds number {reynolds} has to be more than zero")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AirfoilError {}
+
+impl From<io::Error> for AirfoilError {
+    fn from(error: io::Error) -> Self {
+        AirfoilError::Io(error)
+    }
+}
+
+/// section coefficients, moment about the quarter chord
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SectionCoefficients {
+    pub cl: f64,
+    pub cd: f64,
+    pub cm: f64,
+}
+
+impl SectionCoefficients {
+    fn lerp(&self, other: &SectionCoefficients, t: f64) -> SectionCoefficients {
+        SectionCoefficients {
+            cl: self.cl + (other.cl - self.cl) * t,
+            cd: self.cd + (other.cd - self.cd) * t,
+            cm: self.cm + (other.cm - self.cm) * t,
+        }
+    }
+}
+
+/// one run of the aerofoil at a single reynolds number
+#[derive(Debug)]
+pub struct Polar {
+    pub reynolds: f64,
+    /// sorted by alpha, radians
+    points: Vec<(f64, SectionCoefficients)>,
+}
+
+impl Polar {
+    /// alpha in degrees, the order doesn't matter
+    pub fn new(
+        reynolds: f64,
+        mut points: Vec<(f64, SectionCoefficients)>,
+    ) -> Result<Polar, AirfoilError> {
+        if points.len() < 2 {
+            return Err(AirfoilError::TooFewPoints);
+        }
+        let finite = reynolds.is_finite()
+            && points.iter().all(|(alpha, coefficients)| {
+                alpha.is_finite()
+                    && coefficients.cl.is_finite()
+                    && coefficients.cd.is_finite()
+                    && coefficients.cm.is_finite()
+            });
+        if !finite {
+            return Err(AirfoilError::NotFinite { reynolds });
+        }
+        if reynolds <= 0.0 {
+            return Err(AirfoilError::BadReynolds { reynolds });
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let points = points
+            .into_iter()
+            .map(|(alpha, coefficients)| (alpha.to_radians(), coefficients))
+            .collect();
+        Ok(Polar { reynolds, points })
+    }
+
+    /// the polar text XFOIL and XFLR5 write out, a header with the reynolds number then a table under a row of dashes
+    pub fn from_xfoil(text: &str) -> Result<Polar, AirfoilError> {
+        let mut reynolds = None;
+        let mut columns = None;
+        let mut points = Vec::new();
+        let mut in_table = false;
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let trimmed = line.trim();
+            if in_table {
+                if trimmed.is_empty() {
+                    break;
+                }
+                let (alpha, cl, cd, cm) = columns.expect("table starts after the column names");
+                let values = parse_numbers(trimmed.split_whitespace(), line_number)?;
+                points.push(row(&values, alpha, cl, cd, cm, line_number)?);
+            } else if let Some(after) = trimmed.split("Re =").nth(1) {
+                let number: String = after
+                    .split("Ncrit")
+                    .next()
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .collect();
+                reynolds = Some(number.parse::<f64>().map_err(|_| AirfoilError::Parse {
+                    line: line_number,
+                    message: format!("bad reynolds number {number}"),
+                })?);
+            } else if trimmed.to_lowercase().starts_with("alpha") {
+                let names: Vec<_> = trimmed.split_whitespace().collect();
+                columns = Some(find_columns(&names, line_number)?);
+            } else if columns.is_some() && trimmed.starts_with("---") {
+                in_table = true;
+            }
+        }
+        let reynolds = reynolds.ok_or(AirfoilError::Parse {
+            line: 0,
+            message: "no reynolds number in the header".to_string(),
+        })?;
+        Polar::new(reynolds, points)
+    }
+
+    /// alpha only has to be inside the table, ends are held
+    pub fn coefficients(&self, alpha: f64) -> SectionCoefficients {
+        let points = &self.points;
+        match points
+            .iter()
+            .position(|&(point_alpha, _)| point_alpha > alpha)
+        {
+            None => points[points.len() - 1].1,
+            Some(0) => points[0].1,
+            Some(i) => {
+                let (alpha_0, below) = points[i - 1];
+                let (alpha_1, above) = points[i];
+                below.lerp(&above, (alpha - alpha_0) / (alpha_1 - alpha_0))
+            }
+        }
+    }
+
+    /// smallest and largest alpha in the table, radians
+    pub fn alpha_range(&self) -> (f64, f64) {
+        (self.points[0].0, self.points[self.points.len() - 1].0)
+    }
+}
+
+/// polars at several reynolds numbers for the same section
+#[derive(Debug)]
+pub struct Airfoil {
+    /// sorted by reynolds number
+    polars: Vec<Polar>,
+}
+
+impl Airfoil {
+    pub fn new(mut polars: Vec<Polar>) -> Result<Airfoil, AirfoilError> {
+        if polars.is_empty() {
+            return Err(AirfoilError::TooFewPoints);
+        }
+        polars.sort_by(|a, b| a.reynolds.total_cmp(&b.reynolds));
+        let airfoil = Airfoil { polars };
+        let (low, high) = airfoil.alpha_range();
+        if low >= high {
+            return Err(AirfoilError::NoOverlap);
+        }
+        Ok(airfoil)
+    }
+
+    /// one XFOIL or XFLR5 file per reynolds number
+    pub fn from_xfoil_files<P: AsRef<Path>>(paths: &[P]) -> Result<Airfoil, AirfoilError> {
+        let polars = paths
+            .iter()
+            .map(|path| Polar::from_xfoil(&fs::read_to_string(path)?))
+            .collect::<Result<Vec<_>, _>>()?;
+        Airfoil::new(polars)
+    }
+
+    pub fn from_csv_file<P: AsRef<Path>>(path: P) -> Result<Airfoil, AirfoilError> {
+        Airfoil::from_csv(&fs::read_to_string(path)?)
+    }
+
+    /// a header naming reynolds (or re), alpha, cl, cd and cm in any order, alpha in degrees
+    /// rows with the same reynolds number make one polar
+    pub fn from_csv(text: &str) -> Result<Airfoil, AirfoilError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let (_, header) = lines.next().ok_or(AirfoilError::TooFewPoints)?;
+        let names: Vec<_> = header.split(',').map(str::trim).collect();
+        let (alpha, cl, cd, cm) = find_columns(&names, 1)?;
+        let reynolds = column(&names, &["reynolds", "re"], 1)?;
+
+        let mut by_reynolds: Vec<(f64, Vec<(f64, SectionCoefficients)>)> = Vec::new();
+        for (index, line) in lines {
+            let line_number = index + 1;
+            let values = parse_numbers(line.split(',').map(str::trim), line_number)?;
+            let point = row(&values, alpha, cl, cd, cm, line_number)?;
+            let re = *values.get(reynolds).ok_or(AirfoilError::Parse {
+                line: line_number,
+                message: "missing reynolds number".to_string(),
+            })?;
+            match by_reynolds.iter_mut().find(|(polar_re, _)| *polar_re == re) {
+                Some((_, points)) => points.push(point),
+                None => by_reynolds.push((re, vec![point])),
+            }
+        }
+        let polars = by_reynolds
+            .into_iter()
+            .map(|(re, points)| Polar::new(re, points))
+            .collect::<Result<Vec<_>, _>>()?;
+        Airfoil::new(polars)
+    }
+
+    /// straight line in alpha within each polar, and in log reynolds number between them
+    pub fn coefficients(&self, alpha: f64, reynolds: f64) -> SectionCoefficients {
+        let polars = &self.polars;
+        match polars.iter().position(|polar| polar.reynolds > reynolds) {
+            None => polars[polars.len() - 1].coefficients(alpha),
+            Some(0) => polars[0].coefficients(alpha),
+            Some(i) => {
+                let (below, above) = (&polars[i - 1], &polars[i]);
+                let t = (reynolds.ln() - below.reynolds.ln())
+                    / (above.reynolds.ln() - below.reynolds.ln());
+                below
+                    .coefficients(alpha)
+                    .lerp(&above.coefficients(alpha), t)
+            }
+        }
+    }
+
+    /// alpha covered by every polar, radians
+    pub fn alpha_range(&self) -> (f64, f64) {
+        self.polars.iter().map(Polar::alpha_range).fold(
+            (f64::MIN, f64::MAX),
+            |(low, high), (polar_low, polar_high)| (low.max(polar_low), high.min(polar_high)),
+        )
+    }
+}
+
+fn parse_numbers<'a>(
+    fields: impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<Vec<f64>, AirfoilError> {
+    fields
+        .map(|field| {
+            field.parse::<f64>().map_err(|_| AirfoilError::Parse {
+                line,
+                message: format!("{field} isn't a number"),
+            })
+        })
+        .collect()
+}
+
+fn column(names: &[&str], wanted: &[&str], line: usize) -> Result<usize, AirfoilError> {
+    names
+        .iter()
+        .position(|name| wanted.contains(&name.to_lowercase().as_str()))
+        .ok_or(AirfoilError::Parse {
+            line,
+            message: format!("no {} column", wanted[0]),
+        })
+}
+
+fn find_columns(names: &[&str], line: usize) -> Result<(usize, usize, usize, usize), AirfoilError> {
+    Ok((
+        column(names, &["alpha"], line)?,
+        column(names, &["cl"], line)?,
+        column(names, &["cd"], line)?,
+        column(names, &["cm"], line)?,
+    ))
+}
+
+fn row(
+    values: &[f64],
+    alpha: usize,
+    cl: usize,
+    cd: usize,
+    cm: usize,
+    line: usize,
+) -> Result<(f64, SectionCoefficients), AirfoilError> {
+    let get = |index: usize| {
+        values.get(index).copied().ok_or(AirfoilError::Parse {
+            line,
+            message: "row is too short".to_string(),
+        })
+    };
+    Ok((
+        get(alpha)?,
+        SectionCoefficients {
+            cl: get(cl)?,
+            cd: get(cd)?,
+            cm: get(cm)?,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::airfoil::{Airfoil, AirfoilError, Polar};
+
+    const XFOIL: &str = "
+       XFOIL         Version 6.99
+
+ Calculated polar for: NACA 2412
+
+ 1 1 Reynolds number fixed          Mach number fixed
+
+ xtrf =   1.000 (top)        1.000 (bottom)
+ Mach =   0.000     Re =     3.000 e 6     Ncrit =   9.000
+
+  alpha    CL        CD       CDp       CM     Top_Xtr  Bot_Xtr
+ ------- -------- --------- --------- -------- -------- --------
+  -2.000   0.0300   0.00580   0.00120  -0.0520   0.7000   0.2000
+   0.000   0.2500   0.00560   0.00110  -0.0540   0.6000   0.4000
+   4.000   0.7000   0.00650   0.00180  -0.0560   0.4000   0.8000
+";
+
+    const CSV: &str = "re,alpha,cl,cd,cm
+1e6,0,0.2,0.007,-0.05
+1e6,10,1.2,0.015,-0.04
+4e6,0,0.3,0.005,-0.05
+4e6,10,1.3,0.011,-0.04
+";
+
+    #[test]
+    fn reads_an_xfoil_polar() {
+        let under_test = Polar::from_xfoil(XFOIL).unwrap();
+        assert_eq!(under_test.reynolds, 3e6);
+        let coefficients = under_test.coefficients(2.0_f64.to_radians());
+        assert!((coefficients.cl - 0.475).abs() < 1e-12);
+        assert!((coefficients.cd - 0.00605).abs() < 1e-12);
+        assert!((coefficients.cm + 0.055).abs() < 1e-12);
+        let (low, high) = under_test.alpha_range();
+        assert!((low.to_degrees() + 2.0).abs() < 1e-12);
+        assert!((high.to_degrees() - 4.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn interpolates_between_reynolds_numbers() {
+        let under_test = Airfoil::from_csv(CSV).unwrap();
+        let alpha = 5.0_f64.to_radians();
+        assert!((under_test.coefficients(alpha, 1e6).cl - 0.7).abs() < 1e-12);
+        // halfway in log reynolds number
+        assert!((under_test.coefficients(alpha, 2e6).cl - 0.75).abs() < 1e-12);
+        // held at the ends
+        assert!((under_test.coefficients(alpha, 1e7).cl - 0.8).abs() < 1e-12);
+        assert!((under_test.coefficients(alpha, 1e5).cd - 0.011).abs() < 1e-12);
+    }
+
+    #[test]
+    fn bad_files_say_where() {
+        let error = Airfoil::from_csv("re,alpha,cl,cd,cm\n1e6,0,x,0,0\n1e6,1,0,0,0").unwrap_err();
+        assert!(matches!(error, AirfoilError::Parse { line: 2, .. }));
+        let error = Airfoil::from_csv("re,alpha,cl,cd\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: no cm column");
+        let error = Airfoil::from_csv("re,alpha,cl,cd,cm\n1e6,0,0,0,0").unwrap_err();
+        assert!(matches!(error, AirfoilError::TooFewPoints));
+        assert!(matches!(
+            Airfoil::from_csv_file("nowhere.csv"),
+            Err(AirfoilError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_nan_cells() {
+        let error = Airfoil::from_csv("re,alpha,cl,cd,cm\n1e6,0,NaN,0,0\n1e6,1,0,0,0").unwrap_err();
+        assert!(matches!(error, AirfoilError::NotFinite { reynolds } if reynolds == 1e6));
+        let error = Polar::from_xfoil(&XFOIL.replace("0.7000", "inf")).unwrap_err();
+        assert!(matches!(error, AirfoilError::NotFinite { .. }));
+    }
+
+    #[test]
+    fn rejects_reynolds_numbers_that_arent_positive() {
+        let error =
+            Airfoil::from_csv("re,alpha,cl,cd,cm\n0,0,0,0.01,0\n0,1,0.1,0.01,0").unwrap_err();
+        assert!(matches!(error, AirfoilError::BadReynolds { reynolds } if reynolds == 0.0));
+        let error = Polar::from_xfoil(&XFOIL.replace("Re =", "Re = -")).unwrap_err();
+        assert!(matches!(error, AirfoilError::BadReynolds { .. }));
+    }
+
+    #[test]
+    fn rejects_polars_that_dont_overlap() {
+        let error = Airfoil::from_csv(
+            "re,alpha,cl,cd,cm
+1e5,-5,-0.3,0.01,0
+1e5,5,0.7,0.01,0
+1e6,8,1.0,0.01,0
+1e6,15,1.4,0.02,0
+",
+        )
+        .unwrap_err();
+        assert!(matches!(error, AirfoilError::NoOverlap));
+    }
+}
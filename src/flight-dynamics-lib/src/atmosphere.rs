@@ -0,0 +1,86 @@
+/// gas constant for dry air, J/kg/K
+const GAS_CONSTANT: f64 = 287.053;
+/// ratio of specific heats for air
+const GAMMA: f64 = 1.4;
+const SEA_LEVEL_TEMPERATURE: f64 = 288.15;
+const SEA_LEVEL_PRESSURE: f64 = 101325.0;
+/// temperature drop per metre in the troposphere, K/m
+const LAPSE_RATE: f64 = 0.0065;
+/// where the temperature stops dropping, m
+const TROPOPAUSE: f64 = 11000.0;
+const GRAVITY: f64 = 9.80665;
+
+/// the air at some altitude on a standard day
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Atmosphere {
+    /// K
+    pub temperature: f64,
+    /// Pa
+    pub pressure: f64,
+    /// kg/m^3
+    pub density: f64,
+    /// m/s
+    pub speed_of_sound: f64,
+    /// m^2/s
+    pub kinematic_viscosity: f64,
+}
+
+impl Atmosphere {
+    /// international standard atmosphere, troposphere and the isothermal layer above it
+    /// altitude is geopotential metres above sea level, anything above 20 km is treated as 20 km
+    pub fn standard(altitude: f64) -> Atmosphere {
+        let altitude = altitude.min(20000.0);
+        let exponent = GRAVITY / (GAS_CONSTANT * LAPSE_RATE);
+        let (temperature, pressure) = if altitude <= TROPOPAUSE {
+            let temperature = SEA_LEVEL_TEMPERATURE - LAPSE_RATE * altitude;
+            let pressure =
+                SEA_LEVEL_PRESSURE * (temperature / SEA_LEVEL_TEMPERATURE).powf(exponent);
+            (temperature, pressure)
+        } else {
+            let temperature = SEA_LEVEL_TEMPERATURE - LAPSE_RATE * TROPOPAUSE;
+            let tropopause_pressure =
+                SEA_LEVEL_PRESSURE * (temperature / SEA_LEVEL_TEMPERATURE).powf(exponent);
+            let pressure = tropopause_pressure
+                * (-GRAVITY * (altitude - TROPOPAUSE) / (GAS_CONSTANT * temperature)).exp();
+            (temperature, pressure)
+        };
+        let density = pressure / (GAS_CONSTANT * temperature);
+        // sutherland's law
+        let dynamic_viscosity = 1.458e-6 * temperature.powf(1.5) / (temperature + 110.4);
+        Atmosphere {
+            temperature,
+            pressure,
+            density,
+            speed_of_sound: (GAMMA * GAS_CONSTANT * temperature).sqrt(),
+            kinematic_viscosity: dynamic_viscosity / density,
+        }
+    }
+
+    pub fn sea_level() -> Atmosphere {
+        Atmosphere::standard(0.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::atmosphere::Atmosphere;
+
+    #[test]
+    fn sea_level_matches_the_tables() {
+        let under_test = Atmosphere::sea_level();
+        assert!((under_test.density - 1.225).abs() < 1e-3);
+        assert!((under_test.speed_of_sound - 340.294).abs() < 1e-3);
+        assert!((under_test.kinematic_viscosity - 1.46e-5).abs() < 0.01e-5);
+    }
+
+    #[test]
+    fn thinner_and_colder_going_up() {
+        let tropopause = Atmosphere::standard(11000.0);
+        assert!((tropopause.temperature - 216.65).abs() < 1e-9);
+        assert!((tropopause.density - 0.3639).abs() < 1e-3);
+        assert!((tropopause.speed_of_sound - 295.07).abs() < 0.01);
+        let above = Atmosphere::standard(15000.0);
+        assert_eq!(above.temperature, tropopause.temperature);
+        assert!((above.pressure - 12045.0).abs() < 10.0);
+    }
+}
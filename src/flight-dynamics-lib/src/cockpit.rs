@@ -1,8 +1,20 @@
+#[derive(Clone)]
 pub struct Cockpit {
     pub throttle: f64,
     pub elevator: f64,
     pub roll: f64,
+    /// rudder pedals, positive is left, also steers the nose wheel
     pub yaw: f64,
+    /// toe brakes, 0 is off and 1 is full
+    pub brake_left: f64,
+    pub brake_right: f64,
+    /// gear lever, only moves retractable gear
+    pub gear_down: bool,
+    /// flap lever stage, 0 is up
+    pub flaps: usize,
+    pub slats: bool,
+    /// speedbrake lever, 0 is stowed and 1 is fully up
+    pub spoilers: f64,
 }
 
 impl Cockpit {
@@ -12,11 +24,25 @@ impl Cockpit {
             elevator: 0.0,
             roll: 0.0,
             yaw: 0.0,
+            brake_left: 0.0,
+            brake_right: 0.0,
+            gear_down: true,
+            flaps: 0,
+            slats: false,
+            spoilers: 0.0,
         }
     }
     pub fn zero(&mut self) {
         self.elevator = 0.0;
         self.roll = 0.0;
         self.yaw = 0.0;
+        self.brake_left = 0.0;
+        self.brake_right = 0.0;
+    }
+}
+
+impl Default for Cockpit {
+    fn default() -> Self {
+        Self::new()
     }
 }
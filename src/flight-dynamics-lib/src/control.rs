@@ -0,0 +1,195 @@
+use std::f64::consts::PI;
+
+use crate::{cockpit::Cockpit, side::Side};
+
+/// how much of each cockpit axis moves a surface, the sum is clamped to -1..1
+/// positive moves the trailing edge down
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mixing {
+    pub elevator: f64,
+    pub roll: f64,
+    pub yaw: f64,
+}
+
+impl Mixing {
+    /// pulling back lifts the trailing edge
+    pub fn elevator() -> Mixing {
+        Mixing {
+            elevator: -1.0,
+            roll: 0.0,
+            yaw: 0.0,
+        }
+    }
+
+    /// rolling left lifts the left aileron and drops the right
+    pub fn aileron(side: Side) -> Mixing {
+        let roll = match side {
+            Side::Left => -1.0,
+            Side::Right => 1.0,
+        };
+        Mixing {
+            elevator: 0.0,
+            roll,
+            yaw: 0.0,
+        }
+    }
+
+    /// for a fin rolled so its lift points right, left rudder pushes the tail right
+    pub fn rudder() -> Mixing {
+        Mixing {
+            elevator: 0.0,
+            roll: 0.0,
+            yaw: 1.0,
+        }
+    }
+
+    /// elevator and aileron together for a tailless wing, half of each so full pitch and roll don't saturate
+    pub fn elevon(side: Side) -> Mixing {
+        Mixing {
+            elevator: -0.5,
+            roll: 0.5 * Mixing::aileron(side).roll,
+            yaw: 0.0,
+        }
+    }
+
+    /// -1 to 1
+    pub fn command(&self, controls: &Cockpit) -> f64 {
+        (self.elevator * controls.elevator + self.roll * controls.roll + self.yaw * controls.yaw)
+            .clamp(-1.0, 1.0)
+    }
+}
+
+/// a hinged flap along the trailing edge of a surface
+pub struct ControlSurface {
+    /// how much of the chord moves
+    pub chord_fraction: f64,
+    /// most the trailing edge goes down, rad
+    pub max_down: f64,
+    /// most the trailing edge goes up, rad, as a positive number
+    pub max_up: f64,
+    pub mixing: Mixing,
+    /// trailing edge down is positive, rad
+    deflection: f64,
+}
+
+impl ControlSurface {
+    pub fn new(chord_fraction: f64, max_down: f64, max_up: f64, mixing: Mixing) -> ControlSurface {
+        ControlSurface {
+            chord_fraction,
+            max_down,
+            max_up,
+            mixing,
+            deflection: 0.0,
+        }
+    }
+
+    pub fn deflection(&self) -> f64 {
+        self.deflection
+    }
+
+    /// move to wherever the cockpit says
+    pub fn update_controls(&mut self, controls: &Cockpit) {
+        let command = self.mixing.command(controls);
+        self.deflection = if command >= 0.0 {
+            command * self.max_down
+        } else {
+            command * self.max_up
+        };
+    }
+
+    /// how far the deflection moves the lift curve, as a change in alpha
+    pub fn alpha_shift(&self) -> f64 {
+        plain_flap(self.chord_fraction, self.deflection).alpha_shift
+    }
+
+    /// a flap going down pitches the section nose down
+    pub fn pitching_moment(&self) -> f64 {
+        plain_flap(self.chord_fraction, self.deflection).pitching_moment
+    }
+
+    /// extra profile drag from sticking the flap out into the air
+    pub fn drag(&self) -> f64 {
+        plain_flap(self.chord_fraction, self.deflection).drag
+    }
+}
+
+/// what a hinged trailing edge does to the section
+pub struct FlapEffect {
+    /// as a change in alpha, rad
+    pub alpha_shift: f64,
+    pub pitching_moment: f64,
+    pub drag: f64,
+}
+
+/// thin aerofoil theory for a hinged trailing edge, the hinge sits at an angle round from the leading edge
+pub fn plain_flap(chord_fraction: f64, deflection: f64) -> FlapEffect {
+    let theta = (2.0 * chord_fraction - 1.0).acos();
+    let effectiveness = 1.0 - (theta - theta.sin()) / PI;
+    FlapEffect {
+        alpha_shift: effectiveness * deflection,
+        pitching_moment: -0.5 * theta.sin() * (1.0 - theta.cos()) * deflection,
+        drag: chord_fraction * deflection.sin().powi(2),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cockpit::Cockpit,
+        control::{ControlSurface, Mixing},
+        side::Side,
+    };
+
+    #[test]
+    fn pulling_back_lifts_the_elevator() {
+        let mut under_test = ControlSurface::new(0.4, 0.26, 0.44, Mixing::elevator());
+        let mut controls = Cockpit::new();
+        controls.elevator = 1.0;
+        under_test.update_controls(&controls);
+        assert_eq!(under_test.deflection(), -0.44);
+        controls.elevator = -0.5;
+        under_test.update_controls(&controls);
+        assert_eq!(under_test.deflection(), 0.13);
+    }
+
+    #[test]
+    fn ailerons_move_opposite_ways() {
+        let mut left = ControlSurface::new(0.25, 0.26, 0.26, Mixing::aileron(Side::Left));
+        let mut right = ControlSurface::new(0.25, 0.26, 0.26, Mixing::aileron(Side::Right));
+        let mut controls = Cockpit::new();
+        controls.roll = 0.5;
+        left.update_controls(&controls);
+        right.update_controls(&controls);
+        assert!(left.deflection() < 0.0);
+        assert_eq!(left.deflection(), -right.deflection());
+    }
+
+    #[test]
+    fn elevons_mix_and_clamp() {
+        let mut left = ControlSurface::new(0.25, 0.3, 0.3, Mixing::elevon(Side::Left));
+        let mut controls = Cockpit::new();
+        controls.elevator = 1.0;
+        left.update_controls(&controls);
+        assert!((left.deflection() + 0.15).abs() < 1e-12);
+        controls.roll = 1.0;
+        left.update_controls(&controls);
+        assert!((left.deflection() + 0.3).abs() < 1e-12);
+        controls.elevator = 3.0;
+        left.update_controls(&controls);
+        assert!((left.deflection() + 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn whole_chord_flap_is_fully_effective() {
+        let mut under_test = ControlSurface::new(1.0, 0.2, 0.2, Mixing::rudder());
+        let mut controls = Cockpit::new();
+        controls.yaw = 1.0;
+        under_test.update_controls(&controls);
+        assert!((under_test.alpha_shift() - 0.2).abs() < 1e-12);
+        under_test.chord_fraction = 0.25;
+        // about 60 percent for a quarter chord flap
+        assert!((under_test.alpha_shift() / 0.2 - 0.61).abs() < 0.01);
+        assert!(under_test.pitching_moment() < 0.0);
+        assert!(under_test.drag() > 0.0);
+    }
+}
@@ -0,0 +1,439 @@
+use nalgebra::Vector3;
+
+use crate::{earth::EarthModel, integrator::RigidBodyState, side::Side};
+
+/// airframe against the ground, much stiffer than a tyre
+const AIRFRAME_STIFFNESS: f64 = 300000.0;
+const AIRFRAME_DAMPING: f64 = 15000.0;
+const SCRAPING_FRICTION: f64 = 0.5;
+const CREEP_SPEED: f64 = 1.0;
+
+/// touchdown sink rates, m/s
+const FIRM_LANDING: f64 = 2.0;
+const HARD_LANDING: f64 = 3.0;
+const GEAR_COLLAPSE: f64 = 5.0;
+/// anything hitting the ground faster than this isn't walking away
+const UNSURVIVABLE: f64 = 8.0;
+/// airframe touching the ground faster than this is bent, slower is scratched paint
+const SCRAPE: f64 = 1.0;
+/// flight paths shallower than this skid along the ground, rad
+const GLANCING: f64 = 15.0_f64.to_radians();
+/// steeper than this the nose digs in and the whole speed goes into the impact, rad
+const DIGGING_IN: f64 = 30.0_f64.to_radians();
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Minor,
+    Substantial,
+    Destroyed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SimEventKind {
+    TerrainImpact { impact_speed: f64 },
+    HardLanding { sink_rate: f64 },
+    GearUpLanding,
+    WingtipStrike { side: Side },
+    TailStrike,
+    PropStrike,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SimEvent {
+    /// sim seconds since the plane was made
+    pub time: f64,
+    pub kind: SimEventKind,
+    pub severity: Severity,
+    /// world position of whatever hit
+    pub position: Vector3<f64>,
+}
+
+/// bits of the airframe that shouldn't touch the ground
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrikeKind {
+    Wingtip(Side),
+    Tail,
+    Propeller,
+    Belly,
+}
+
+pub struct StrikePoint {
+    pub kind: StrikeKind,
+    /// relative to the datum in body coordinates
+    pub position: Vector3<f64>,
+}
+
+/// the ground pushing on a bit of airframe
+pub struct AirframeContact {
+    /// body coordinates
+    pub force: Vector3<f64>,
+    /// body coordinates, about the cg
+    pub moment: Vector3<f64>,
+    /// of the point that touched, world coordinates
+    pub velocity: Vector3<f64>,
+    /// world coordinates
+    pub position: Vector3<f64>,
+}
+
+impl StrikePoint {
+    pub fn new(kind: StrikeKind, position: Vector3<f64>) -> StrikePoint {
+        StrikePoint { kind, position }
+    }
+
+    /// a stiff spring with scraping friction, so a plane on its belly slides along instead of sinking
+    pub fn contact(
+        &self,
+        state: &RigidBodyState,
+        cg: &Vector3<f64>,
+        earth: &dyn EarthModel,
+    ) -> Option<AirframeContact> {
+        let rotation = state.transform.rotation;
+        let arm = self.position - cg;
+        let position = state.transform.translation.vector + rotation * arm;
+        let depth = -earth.height_above_ground(&position);
+        if depth <= 0.0 {
+            return None;
+        }
+
+        let point_velocity = state.velocity + rotation * state.angular_velocity.cross(&arm);
+        let normal = (AIRFRAME_STIFFNESS * depth + AIRFRAME_DAMPING * point_velocity.z).max(0.0);
+
+        let sliding = Vector3::new(point_velocity.x, point_velocity.y, 0.0);
+        let speed = sliding.norm();
+        let friction = if speed > 0.0 {
+            -sliding / speed * SCRAPING_FRICTION * normal * (speed / CREEP_SPEED).min(1.0)
+        } else {
+            Vector3::zeros()
+        };
+
+        let force = rotation.inverse() * (Vector3::new(0.0, 0.0, -normal) + friction);
+        Some(AirframeContact {
+            force,
+            moment: arm.cross(&force),
+            velocity: point_velocity,
+            position,
+        })
+    }
+}
+
+/// watches what's touching the ground from step to step and reports the moment something new hits
+pub struct DamageMonitor {
+    gear_in_contact: Vec<bool>,
+    points_in_contact: Vec<bool>,
+    events: Vec<SimEvent>,
+    worst: Option<Severity>,
+}
+
+impl DamageMonitor {
+    pub fn new() -> DamageMonitor {
+        DamageMonitor {
+            gear_in_contact: Vec::new(),
+            points_in_contact: Vec::new(),
+            events: Vec::new(),
+            worst: None,
+        }
+    }
+
+    /// gear contacts are the world velocity and position of each wheel on the ground,
+    /// strike contacts line up with the plane's strike points
+    pub fn check(
+        &mut self,
+        time: f64,
+        gear_contacts: &[Option<(Vector3<f64>, Vector3<f64>)>],
+        strike_contacts: &[(StrikeKind, Option<AirframeContact>)],
+        gear_down: bool,
+    ) {
+        self.gear_in_contact.resize(gear_contacts.len(), false);
+        self.points_in_contact.resize(strike_contacts.len(), false);
+
+        for (i, contact) in gear_contacts.iter().enumerate() {
+            let touching = contact.is_some();
+            if let Some((velocity, position)) = contact
+                && !self.gear_in_contact[i]
+                && let Some(severity) = DamageMonitor::touchdown_severity(velocity.z)
+            {
+                let impact_speed = impact_speed(velocity);
+                self.report(
+                    time,
+                    DamageMonitor::impact_or(
+                        impact_speed,
+                        SimEventKind::HardLanding {
+                            sink_rate: velocity.z,
+                        },
+                    ),
+                    severity.max(DamageMonitor::impact_severity(impact_speed)),
+                    *position,
+                );
+            }
+            self.gear_in_contact[i] = touching;
+        }
+
+        for (i, (kind, contact)) in strike_contacts.iter().enumerate() {
+            let touching = contact.is_some();
+            if let Some(contact) = contact
+                && !self.points_in_contact[i]
+            {
+                let impact_speed = impact_speed(&contact.velocity);
+                let scraped = if contact.velocity.z < SCRAPE {
+                    Severity::Minor
+                } else {
+                    Severity::Substantial
+                };
+                let (event, severity) = match kind {
+                    StrikeKind::Wingtip(side) => {
+                        (SimEventKind::WingtipStrike { side: *side }, scraped)
+                    }
+                    StrikeKind::Tail => (SimEventKind::TailStrike, scraped),
+                    StrikeKind::Propeller => (SimEventKind::PropStrike, Severity::Substantial),
+                    StrikeKind::Belly if !gear_down => {
+                        (SimEventKind::GearUpLanding, Severity::Substantial)
+                    }
+                    StrikeKind::Belly => (
+                        SimEventKind::TerrainImpact { impact_speed },
+                        Severity::Substantial,
+                    ),
+                };
+                self.report(
+                    time,
+                    DamageMonitor::impact_or(impact_speed, event),
+                    severity.max(DamageMonitor::impact_severity(impact_speed)),
+                    contact.position,
+                );
+            }
+            self.points_in_contact[i] = touching;
+        }
+    }
+
+    fn touchdown_severity(sink_rate: f64) -> Option<Severity> {
+        if sink_rate >= GEAR_COLLAPSE {
+            Some(Severity::Destroyed)
+        } else if sink_rate >= HARD_LANDING {
+            Some(Severity::Substantial)
+        } else if sink_rate >= FIRM_LANDING {
+            Some(Severity::Minor)
+        } else {
+            None
+        }
+    }
+
+    /// going fast enough, it doesn't matter which bit hit first
+    fn impact_or(impact_speed: f64, kind: SimEventKind) -> SimEventKind {
+        if impact_speed >= UNSURVIVABLE {
+            SimEventKind::TerrainImpact { impact_speed }
+        } else {
+            kind
+        }
+    }
+
+    fn impact_severity(impact_speed: f64) -> Severity {
+        if impact_speed >= UNSURVIVABLE {
+            Severity::Destroyed
+        } else {
+            Severity::Minor
+        }
+    }
+
+    fn report(
+        &mut self,
+        time: f64,
+        kind: SimEventKind,
+        severity: Severity,
+        position: Vector3<f64>,
+    ) {
+        self.worst = self.worst.max(Some(severity));
+        self.events.push(SimEvent {
+            time,
+            kind,
+            severity,
+            position,
+        });
+    }
+
+    /// hands over everything that has happened since the last call
+    pub fn drain_events(&mut self) -> Vec<SimEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// the worst thing that has happened so far
+    pub fn worst_severity(&self) -> Option<Severity> {
+        self.worst
+    }
+}
+
+/// how hard something hit, the speed into the ground for a glancing blow,
+/// easing up to the whole speed of the point once it's coming in steep enough to dig in
+fn impact_speed(velocity: &Vector3<f64>) -> f64 {
+    let speed = velocity.norm();
+    let sink_rate = velocity.z.max(0.0);
+    if speed == 0.0 {
+        return 0.0;
+    }
+    let steepness = (sink_rate / speed).asin();
+    let t = ((steepness - GLANCING) / (DIGGING_IN - GLANCING)).clamp(0.0, 1.0);
+    let digging_in = t * t * (3.0 - 2.0 * t);
+    sink_rate + (speed - sink_rate) * digging_in
+}
+
+impl Default for DamageMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::{Isometry3, Vector3};
+
+    use crate::{
+        damage::{AirframeContact, DamageMonitor, Severity, SimEventKind, StrikeKind, StrikePoint},
+        earth::FlatEarth,
+        integrator::RigidBodyState,
+        side::Side,
+    };
+
+    fn hit(kind: StrikeKind, sink_rate: f64) -> (StrikeKind, Option<AirframeContact>) {
+        hit_moving(kind, Vector3::new(0.0, 0.0, sink_rate))
+    }
+
+    fn hit_moving(
+        kind: StrikeKind,
+        velocity: Vector3<f64>,
+    ) -> (StrikeKind, Option<AirframeContact>) {
+        (
+            kind,
+            Some(AirframeContact {
+                force: Vector3::zeros(),
+                moment: Vector3::zeros(),
+                velocity,
+                position: Vector3::zeros(),
+            }),
+        )
+    }
+
+    #[test]
+    fn gentle_touchdown_is_quiet() {
+        let mut under_test = DamageMonitor::new();
+        under_test.check(0.0, &[None, None], &[], true);
+        under_test.check(
+            0.1,
+            &[Some((Vector3::new(30.0, 0.0, 1.0), Vector3::zeros())), None],
+            &[],
+            true,
+        );
+        assert!(under_test.drain_events().is_empty());
+        assert_eq!(under_test.worst_severity(), None);
+    }
+
+    #[test]
+    fn hard_landing_reported_once() {
+        let mut under_test = DamageMonitor::new();
+        under_test.check(0.0, &[None], &[], true);
+        under_test.check(
+            0.1,
+            &[Some((Vector3::new(0.0, 0.0, 3.5), Vector3::zeros()))],
+            &[],
+            true,
+        );
+        under_test.check(
+            0.2,
+            &[Some((Vector3::zeros(), Vector3::zeros()))],
+            &[],
+            true,
+        );
+        let events = under_test.drain_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, SimEventKind::HardLanding { sink_rate: 3.5 });
+        assert_eq!(events[0].severity, Severity::Substantial);
+        assert!(under_test.drain_events().is_empty());
+    }
+
+    #[test]
+    fn fast_impact_destroys_whatever_hits() {
+        let mut under_test = DamageMonitor::new();
+        under_test.check(
+            1.0,
+            &[],
+            &[hit(StrikeKind::Wingtip(Side::Left), 12.0)],
+            true,
+        );
+        let events = under_test.drain_events();
+        assert_eq!(
+            events[0].kind,
+            SimEventKind::TerrainImpact { impact_speed: 12.0 }
+        );
+        assert_eq!(under_test.worst_severity(), Some(Severity::Destroyed));
+    }
+
+    #[test]
+    fn steep_nose_first_impact_counts_the_whole_speed() {
+        // 10 m/s at 45 degrees only sinks at 7
+        let steep = Vector3::new(7.07, 0.0, 7.07);
+        let mut under_test = DamageMonitor::new();
+        under_test.check(0.0, &[], &[hit_moving(StrikeKind::Propeller, steep)], false);
+        let events = under_test.drain_events();
+        assert!(matches!(
+            events[0].kind,
+            SimEventKind::TerrainImpact { impact_speed } if impact_speed > 9.9
+        ));
+        assert_eq!(under_test.worst_severity(), Some(Severity::Destroyed));
+        // the same sink sliding in fast and flat is a prop strike
+        let shallow = Vector3::new(40.0, 0.0, 7.0);
+        let mut under_test = DamageMonitor::new();
+        under_test.check(
+            0.0,
+            &[],
+            &[hit_moving(StrikeKind::Propeller, shallow)],
+            false,
+        );
+        assert_eq!(under_test.drain_events()[0].kind, SimEventKind::PropStrike);
+        assert_eq!(under_test.worst_severity(), Some(Severity::Substantial));
+    }
+
+    #[test]
+    fn strikes_are_named() {
+        let mut under_test = DamageMonitor::new();
+        under_test.check(
+            0.0,
+            &[],
+            &[
+                hit(StrikeKind::Wingtip(Side::Right), 0.5),
+                hit(StrikeKind::Tail, 1.5),
+                hit(StrikeKind::Propeller, 0.1),
+                hit(StrikeKind::Belly, 0.5),
+            ],
+            false,
+        );
+        let events = under_test.drain_events();
+        let kinds: Vec<_> = events
+            .iter()
+            .map(|event| (event.kind, event.severity))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    SimEventKind::WingtipStrike { side: Side::Right },
+                    Severity::Minor
+                ),
+                (SimEventKind::TailStrike, Severity::Substantial),
+                (SimEventKind::PropStrike, Severity::Substantial),
+                (SimEventKind::GearUpLanding, Severity::Substantial),
+            ]
+        );
+    }
+
+    #[test]
+    fn airframe_contact_pushes_up_and_scrapes() {
+        let under_test = StrikePoint::new(StrikeKind::Belly, Vector3::new(0.0, 0.0, 0.5));
+        let state = RigidBodyState::new(
+            Isometry3::new(Vector3::new(0.0, 0.0, -0.45), Vector3::zeros()),
+            Vector3::new(20.0, 0.0, 0.0),
+            Vector3::zeros(),
+        );
+        let contact = under_test
+            .contact(&state, &Vector3::zeros(), &FlatEarth::new())
+            .unwrap();
+        assert!(contact.force.z < 0.0);
+        assert!((contact.force.x / contact.force.z - 0.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+
+/// longest the wake takes to get back to the tail, slower than this and it's barely flying anyway
+const MAX_DELAY: f64 = 1.0;
+/// past the stall the wing stops turning the air down any more, rad
+const MAX_WING_ALPHA: f64 = 0.35;
+
+/// the main wing bends the air down before it gets to the tail, so the tail sees less alpha
+/// epsilon = epsilon0 + de/da * alpha, using the alpha the wing had when that air went past it
+pub struct Downwash {
+    /// downwash with the wing at zero alpha, rad
+    pub epsilon0: f64,
+    /// about 2 * lift slope / (pi * aspect ratio) for the main wing
+    pub depsilon_dalpha: f64,
+    /// from the main wing back to the tail, m
+    pub tail_arm: f64,
+    /// (time, main wing alpha) at the end of each step, oldest first
+    history: VecDeque<(f64, f64)>,
+}
+
+impl Downwash {
+    pub fn new(epsilon0: f64, depsilon_dalpha: f64, tail_arm: f64) -> Downwash {
+        Downwash {
+            epsilon0,
+            depsilon_dalpha,
+            tail_arm,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// no wing in front, the tail sees clean air
+    pub fn none() -> Downwash {
+        Downwash::new(0.0, 0.0, 0.0)
+    }
+
+    /// remember the main wing's alpha at the end of a step
+    pub fn record(&mut self, time: f64, alpha: f64) {
+        self.history.push_back((time, alpha));
+        // keep one sample older than the longest delay to interpolate from
+        while self
+            .history
+            .get(1)
+            .is_some_and(|&(second_oldest, _)| second_oldest < time - MAX_DELAY)
+        {
+            self.history.pop_front();
+        }
+    }
+
+    /// how long the air takes to get from the wing to the tail, s
+    pub fn delay(&self, airspeed: f64) -> f64 {
+        if airspeed <= 0.0 {
+            return MAX_DELAY;
+        }
+        (self.tail_arm / airspeed).min(MAX_DELAY)
+    }
+
+    /// angle the air at the tail is bent down by, rad
+    /// alpha is the main wing's right now, only used until there's enough history to look back on
+    pub fn angle(&self, time: f64, alpha: f64, airspeed: f64) -> f64 {
+        let delayed = self.alpha_at(time - self.delay(airspeed)).unwrap_or(alpha);
+        self.epsilon0 + self.depsilon_dalpha * delayed.clamp(-MAX_WING_ALPHA, MAX_WING_ALPHA)
+    }
+
+    /// the wing's alpha at some time in the past, straight lines between samples
+    fn alpha_at(&self, time: f64) -> Option<f64> {
+        let &(oldest_time, oldest_alpha) = self.history.front()?;
+        if time <= oldest_time {
+            return Some(oldest_alpha);
+        }
+        let after = self
+            .history
+            .iter()
+            .position(|&(sample_time, _)| sample_time >= time)?;
+        let (t0, a0) = self.history[after - 1];
+        let (t1, a1) = self.history[after];
+        Some(a0 + (a1 - a0) * (time - t0) / (t1 - t0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::downwash::Downwash;
+
+    #[test]
+    fn steady_downwash_is_linear_in_alpha() {
+        let under_test = Downwash::new(0.01, 0.4, 5.0);
+        assert!((under_test.angle(0.0, 0.1, 50.0) - 0.05).abs() < 1e-12);
+        assert!((under_test.angle(0.0, 0.0, 50.0) - 0.01).abs() < 1e-12);
+        // stops growing once the wing has stalled
+        assert_eq!(
+            under_test.angle(0.0, 1.0, 50.0),
+            under_test.angle(0.0, 2.0, 50.0)
+        );
+    }
+
+    #[test]
+    fn tail_sees_the_wing_late() {
+        let mut under_test = Downwash::new(0.0, 0.5, 5.0);
+        // the wing jumps from 0 to 0.1 alpha at 1 second
+        for step in 0..=200 {
+            let time = step as f64 * 0.01;
+            let alpha = if time < 1.0 { 0.0 } else { 0.1 };
+            under_test.record(time, alpha);
+            // at 50 m/s the air takes a tenth of a second to get to the tail
+            let angle = under_test.angle(time, alpha, 50.0);
+            if time < 1.095 {
+                assert!(angle < 0.05, "{time} {angle}");
+            } else {
+                assert!((angle - 0.05).abs() < 1e-12, "{time} {angle}");
+            }
+        }
+        assert!(under_test.history.len() < 120);
+    }
+}
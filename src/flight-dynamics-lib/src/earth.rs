@@ -0,0 +1,353 @@
+use nalgebra::{Matrix3, Vector3};
+
+/// where something is on the real earth, angles in radians, altitude above the ellipsoid in m
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Geodetic {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+}
+
+impl Geodetic {
+    pub fn from_degrees(latitude: f64, longitude: f64, altitude: f64) -> Geodetic {
+        Geodetic {
+            latitude: latitude.to_radians(),
+            longitude: longitude.to_radians(),
+            altitude,
+        }
+    }
+}
+
+/// the sim runs in a north east down frame fixed to the ground at the model's origin,
+/// this says what gravity and that frame's motion do to things moving in it
+pub trait EarthModel: Send + Sync {
+    /// gravitational acceleration at a local position, m/s^2 in the local frame
+    fn gravity(&self, position: &Vector3<f64>) -> Vector3<f64>;
+
+    /// coriolis and centripetal acceleration from the local frame spinning with the earth
+    fn frame_acceleration(
+        &self,
+        _position: &Vector3<f64>,
+        _velocity: &Vector3<f64>,
+    ) -> Vector3<f64> {
+        Vector3::zeros()
+    }
+
+    /// latitude, longitude and altitude of a local position
+    fn geodetic(&self, position: &Vector3<f64>) -> Geodetic;
+
+    /// how far a local position is above the ground, which is at the origin's altitude everywhere
+    fn height_above_ground(&self, position: &Vector3<f64>) -> f64;
+}
+
+/// constant g straight down, fine for flights around the airfield
+pub struct FlatEarth {
+    pub g: f64,
+    pub origin: Geodetic,
+}
+
+impl FlatEarth {
+    const MEAN_RADIUS: f64 = 6371008.8;
+    /// cos latitude under this is close enough to a pole, about 6 cm away from it
+    const POLE: f64 = 1e-8;
+
+    pub fn new() -> FlatEarth {
+        FlatEarth {
+            g: 9.81,
+            origin: Geodetic::from_degrees(0.0, 0.0, 0.0),
+        }
+    }
+
+    pub fn at(origin: Geodetic) -> FlatEarth {
+        FlatEarth {
+            origin,
+            ..FlatEarth::new()
+        }
+    }
+}
+
+impl Default for FlatEarth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EarthModel for FlatEarth {
+    fn gravity(&self, _position: &Vector3<f64>) -> Vector3<f64> {
+        Vector3::new(0.0, 0.0, self.g)
+    }
+
+    /// small angle, good enough for the few km a flat earth makes sense for
+    /// at a pole east and west don't mean anything, so the longitude stays at the origin's
+    fn geodetic(&self, position: &Vector3<f64>) -> Geodetic {
+        let cos_latitude = self.origin.latitude.cos();
+        let longitude = if cos_latitude.abs() > FlatEarth::POLE {
+            self.origin.longitude + position.y / (FlatEarth::MEAN_RADIUS * cos_latitude)
+        } else {
+            self.origin.longitude
+        };
+        Geodetic {
+            latitude: self.origin.latitude + position.x / FlatEarth::MEAN_RADIUS,
+            longitude,
+            altitude: self.origin.altitude - position.z,
+        }
+    }
+
+    /// the ground is the plane z = 0
+    fn height_above_ground(&self, position: &Vector3<f64>) -> f64 {
+        -position.z
+    }
+}
+
+/// the shape and spin of the earth
+pub struct Ellipsoid {
+    pub semi_major_axis: f64,
+    pub flattening: f64,
+    /// gravitational parameter, m^3/s^2
+    pub gm: f64,
+    /// second zonal harmonic, how much the bulge pulls
+    pub j2: f64,
+    /// rad/s
+    pub rotation_rate: f64,
+}
+
+impl Ellipsoid {
+    pub fn wgs84() -> Ellipsoid {
+        Ellipsoid {
+            semi_major_axis: 6378137.0,
+            flattening: 1.0 / 298.257223563,
+            gm: 3.986004418e14,
+            j2: 1.08262982e-3,
+            rotation_rate: 7.292115e-5,
+        }
+    }
+
+    /// a round earth with the same mass and spin, no bulge
+    pub fn spherical() -> Ellipsoid {
+        Ellipsoid {
+            semi_major_axis: 6371008.8,
+            flattening: 0.0,
+            j2: 0.0,
+            ..Ellipsoid::wgs84()
+        }
+    }
+
+    fn eccentricity_squared(&self) -> f64 {
+        self.flattening * (2.0 - self.flattening)
+    }
+
+    /// earth centred earth fixed position of a geodetic point
+    pub fn to_ecef(&self, point: &Geodetic) -> Vector3<f64> {
+        let e2 = self.eccentricity_squared();
+        let (sin_lat, cos_lat) = point.latitude.sin_cos();
+        let (sin_lon, cos_lon) = point.longitude.sin_cos();
+        let prime_vertical = self.semi_major_axis / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+        Vector3::new(
+            (prime_vertical + point.altitude) * cos_lat * cos_lon,
+            (prime_vertical + point.altitude) * cos_lat * sin_lon,
+            (prime_vertical * (1.0 - e2) + point.altitude) * sin_lat,
+        )
+    }
+
+    /// iterates on latitude, converges to well under a mm in a handful of goes
+    pub fn to_geodetic(&self, ecef: &Vector3<f64>) -> Geodetic {
+        let e2 = self.eccentricity_squared();
+        let longitude = ecef.y.atan2(ecef.x);
+        let p = (ecef.x * ecef.x + ecef.y * ecef.y).sqrt();
+        let mut latitude = ecef.z.atan2(p * (1.0 - e2));
+        let mut altitude = 0.0;
+        for _ in 0..6 {
+            let sin_lat = latitude.sin();
+            let prime_vertical = self.semi_major_axis / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+            altitude = if latitude.cos().abs() > 1e-9 {
+                p / latitude.cos() - prime_vertical
+            } else {
+                ecef.z.abs() - prime_vertical * (1.0 - e2)
+            };
+            latitude = ecef
+                .z
+                .atan2(p * (1.0 - e2 * prime_vertical / (prime_vertical + altitude)));
+        }
+        Geodetic {
+            latitude,
+            longitude,
+            altitude,
+        }
+    }
+
+    /// gravitational pull in earth centred coordinates, point mass plus the j2 bulge term
+    pub fn gravitation_ecef(&self, ecef: &Vector3<f64>) -> Vector3<f64> {
+        let r2 = ecef.norm_squared();
+        let r = r2.sqrt();
+        let z2_over_r2 = ecef.z * ecef.z / r2;
+        let bulge = 1.5 * self.j2 * self.semi_major_axis.powi(2) / r2;
+        let scale = -self.gm / (r2 * r);
+        Vector3::new(
+            scale * ecef.x * (1.0 + bulge * (1.0 - 5.0 * z2_over_r2)),
+            scale * ecef.y * (1.0 + bulge * (1.0 - 5.0 * z2_over_r2)),
+            scale * ecef.z * (1.0 + bulge * (3.0 - 5.0 * z2_over_r2)),
+        )
+    }
+}
+
+/// an ellipsoid spinning under a local north east down frame pinned at the origin
+pub struct RotatingEarth {
+    pub ellipsoid: Ellipsoid,
+    pub origin: Geodetic,
+    origin_ecef: Vector3<f64>,
+    /// turns earth centred vectors into the local frame
+    ecef_to_local: Matrix3<f64>,
+}
+
+impl RotatingEarth {
+    pub fn new(ellipsoid: Ellipsoid, origin: Geodetic) -> RotatingEarth {
+        let (sin_lat, cos_lat) = origin.latitude.sin_cos();
+        let (sin_lon, cos_lon) = origin.longitude.sin_cos();
+        #[rustfmt::skip]
+        let ecef_to_local = Matrix3::new(
+            -sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat,
+            -sin_lon, cos_lon, 0.0,
+            -cos_lat * cos_lon, -cos_lat * sin_lon, -sin_lat,
+        );
+        RotatingEarth {
+            origin_ecef: ellipsoid.to_ecef(&origin),
+            ellipsoid,
+            origin,
+            ecef_to_local,
+        }
+    }
+
+    pub fn wgs84(origin: Geodetic) -> RotatingEarth {
+        RotatingEarth::new(Ellipsoid::wgs84(), origin)
+    }
+
+    pub fn spherical(origin: Geodetic) -> RotatingEarth {
+        RotatingEarth::new(Ellipsoid::spherical(), origin)
+    }
+
+    fn ecef(&self, position: &Vector3<f64>) -> Vector3<f64> {
+        self.origin_ecef + self.ecef_to_local.transpose() * position
+    }
+
+    fn earth_rotation_local(&self) -> Vector3<f64> {
+        self.ecef_to_local * Vector3::new(0.0, 0.0, self.ellipsoid.rotation_rate)
+    }
+}
+
+impl EarthModel for RotatingEarth {
+    fn gravity(&self, position: &Vector3<f64>) -> Vector3<f64> {
+        self.ecef_to_local * self.ellipsoid.gravitation_ecef(&self.ecef(position))
+    }
+
+    fn frame_acceleration(&self, position: &Vector3<f64>, velocity: &Vector3<f64>) -> Vector3<f64> {
+        let omega = self.earth_rotation_local();
+        let from_centre = self.ecef_to_local * self.ecef(position);
+        let coriolis = -2.0 * omega.cross(velocity);
+        let centripetal = -omega.cross(&omega.cross(&from_centre));
+        coriolis + centripetal
+    }
+
+    fn geodetic(&self, position: &Vector3<f64>) -> Geodetic {
+        self.ellipsoid.to_geodetic(&self.ecef(position))
+    }
+
+    /// the ground curves away under the local frame, so this is more than -z away from the origin
+    fn height_above_ground(&self, position: &Vector3<f64>) -> f64 {
+        self.geodetic(position).altitude - self.origin.altitude
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+
+    use crate::earth::{EarthModel, FlatEarth, Geodetic, RotatingEarth};
+
+    /// what a plumb bob would feel standing still
+    fn apparent_gravity(earth: &RotatingEarth) -> f64 {
+        let position = Vector3::zeros();
+        (earth.gravity(&position) + earth.frame_acceleration(&position, &Vector3::zeros())).norm()
+    }
+
+    #[test]
+    fn flat_earth_is_constant() {
+        let under_test = FlatEarth::new();
+        assert_eq!(
+            under_test.gravity(&Vector3::new(1000.0, -500.0, -3000.0)),
+            Vector3::new(0.0, 0.0, 9.81)
+        );
+        assert_eq!(
+            under_test.frame_acceleration(&Vector3::zeros(), &Vector3::new(50.0, 0.0, 0.0)),
+            Vector3::zeros()
+        );
+    }
+
+    #[test]
+    fn flat_earth_altitude_and_latitude() {
+        let under_test = FlatEarth::at(Geodetic::from_degrees(52.0, -1.0, 100.0));
+        let place = under_test.geodetic(&Vector3::new(111195.0, 0.0, -400.0));
+        assert!((place.latitude.to_degrees() - 53.0).abs() < 1e-3);
+        assert!((place.longitude.to_degrees() - -1.0).abs() < 1e-9);
+        assert!((place.altitude - 500.0).abs() < 1e-9);
+        assert_eq!(
+            under_test.height_above_ground(&Vector3::new(111195.0, 0.0, -400.0)),
+            400.0
+        );
+    }
+
+    #[test]
+    fn flat_earth_at_the_pole_keeps_its_longitude() {
+        let under_test = FlatEarth::at(Geodetic::from_degrees(90.0, 20.0, 0.0));
+        let place = under_test.geodetic(&Vector3::new(-1000.0, 500.0, 0.0));
+        assert!(place.longitude.is_finite());
+        assert!((place.longitude.to_degrees() - 20.0).abs() < 1e-9);
+        assert!((place.latitude.to_degrees() - 89.991).abs() < 1e-3);
+    }
+
+    #[test]
+    fn wgs84_gravity_equator_and_pole() {
+        let equator = RotatingEarth::wgs84(Geodetic::from_degrees(0.0, 0.0, 0.0));
+        assert!((apparent_gravity(&equator) - 9.780).abs() < 2e-3);
+        let pole = RotatingEarth::wgs84(Geodetic::from_degrees(90.0, 0.0, 0.0));
+        assert!((apparent_gravity(&pole) - 9.832).abs() < 2e-3);
+    }
+
+    #[test]
+    fn gravity_gets_weaker_with_height() {
+        let under_test = RotatingEarth::wgs84(Geodetic::from_degrees(45.0, 0.0, 0.0));
+        let ground = under_test.gravity(&Vector3::zeros()).norm();
+        let high = under_test.gravity(&Vector3::new(0.0, 0.0, -10000.0)).norm();
+        assert!(((ground - high) - 0.0308).abs() < 1e-3);
+        // and points down at the origin
+        let down = under_test.gravity(&Vector3::zeros());
+        assert!(down.x.abs() / down.z < 1e-2);
+    }
+
+    #[test]
+    fn local_position_round_trips_to_latitude_and_longitude() {
+        let origin = Geodetic::from_degrees(51.5, -0.12, 30.0);
+        let under_test = RotatingEarth::wgs84(origin);
+        let here = under_test.geodetic(&Vector3::zeros());
+        assert!((here.latitude - origin.latitude).abs() < 1e-10);
+        assert!((here.longitude - origin.longitude).abs() < 1e-10);
+        assert!((here.altitude - origin.altitude).abs() < 1e-4);
+
+        let north = under_test.geodetic(&Vector3::new(100000.0, 0.0, 0.0));
+        assert!((north.latitude.to_degrees() - 52.4).abs() < 0.01);
+        // a straight line leaves the curved surface behind
+        assert!(north.altitude > origin.altitude + 700.0);
+        let height = under_test.height_above_ground(&Vector3::new(100000.0, 0.0, 0.0));
+        assert!((height - (north.altitude - origin.altitude)).abs() < 1e-9);
+        assert!(under_test.height_above_ground(&Vector3::new(0.0, 0.0, -2.0)) > 1.99);
+    }
+
+    #[test]
+    fn flying_east_at_the_equator_is_lighter() {
+        let under_test = RotatingEarth::spherical(Geodetic::from_degrees(0.0, 0.0, 0.0));
+        let position = Vector3::zeros();
+        let still = under_test.frame_acceleration(&position, &Vector3::zeros());
+        let eastwards = under_test.frame_acceleration(&position, &Vector3::new(0.0, 100.0, 0.0));
+        let eotvos = eastwards - still;
+        assert!((eotvos.z - -2.0 * 7.292115e-5 * 100.0).abs() < 1e-9);
+    }
+}
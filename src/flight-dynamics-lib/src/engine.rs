@@ -3,7 +3,14 @@ pub struct Engine {
 }
 
 impl Engine {
+    #[allow(clippy::needless_return)]
     pub fn new() -> Engine {
         return Engine { max_thrust: 2000.0 };
     }
 }
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
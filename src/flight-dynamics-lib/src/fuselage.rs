@@ -0,0 +1,128 @@
+use std::f64::consts::PI;
+
+use nalgebra::Vector3;
+
+use crate::{aero::AeroComponent, airflow::Airflow};
+
+/// how much of the infinite cylinder's cross flow drag a finite body gets
+const CROSS_FLOW_EFFICIENCY: f64 = 0.7;
+/// where the slender body lift acts, fraction of the length back from the nose
+const POTENTIAL_LIFT_POSITION: f64 = 0.25;
+
+/// a long round body lined up with the x axis
+/// slender body theory gives a lift that acts well forwards, which is destabilising,
+/// and at bigger angles the air across the body separates like it does round a cylinder
+pub struct Fuselage {
+    /// middle of the body, relative to the datum, m
+    pub centre: Vector3<f64>,
+    /// m
+    pub length: f64,
+    /// widest point, m
+    pub diameter: f64,
+    /// skin friction and form drag on the frontal area, head on
+    pub drag_coefficient: f64,
+    /// of a cylinder side on
+    pub cross_flow_drag: f64,
+}
+
+impl Fuselage {
+    pub fn new(centre: Vector3<f64>, length: f64, diameter: f64) -> Fuselage {
+        Fuselage {
+            centre,
+            length,
+            diameter,
+            drag_coefficient: 0.15,
+            cross_flow_drag: 1.2,
+        }
+    }
+
+    pub fn with_drag(mut self, drag_coefficient: f64) -> Fuselage {
+        self.drag_coefficient = drag_coefficient;
+        self
+    }
+
+    fn frontal_area(&self) -> f64 {
+        0.25 * PI * self.diameter.powi(2)
+    }
+}
+
+impl AeroComponent for Fuselage {
+    /// Jorgensen's normal force, sin 2a cos a/2 on the frontal area plus the cross flow drag on the planform
+    fn loads(&self, airflow: &Airflow) -> (Vector3<f64>, Vector3<f64>) {
+        let velocity = airflow.velocity_at(&self.centre);
+        let speed = velocity.norm();
+        if speed == 0.0 {
+            return (Vector3::zeros(), Vector3::zeros());
+        }
+        let dynamic_pressure = 0.5 * airflow.atmosphere.density * speed.powi(2);
+        let cross_flow = Vector3::new(0.0, velocity.y, velocity.z);
+        let cross_flow_speed = cross_flow.norm();
+        // angle between the body and the air, any direction round it
+        let angle = cross_flow_speed.atan2(velocity.x);
+
+        let drag =
+            -velocity / speed * dynamic_pressure * self.frontal_area() * self.drag_coefficient;
+        let (potential, separated) = if cross_flow_speed > 0.0 {
+            let away_from_the_air = -cross_flow / cross_flow_speed;
+            let potential = (2.0 * angle).sin() * (0.5 * angle).cos() * self.frontal_area();
+            let separated = CROSS_FLOW_EFFICIENCY
+                * self.cross_flow_drag
+                * angle.sin().powi(2)
+                * self.length
+                * self.diameter;
+            (
+                away_from_the_air * dynamic_pressure * potential,
+                away_from_the_air * dynamic_pressure * separated,
+            )
+        } else {
+            (Vector3::zeros(), Vector3::zeros())
+        };
+
+        let nose = self.centre + Vector3::new(0.5 * self.length, 0.0, 0.0);
+        let potential_position =
+            nose - Vector3::new(POTENTIAL_LIFT_POSITION * self.length, 0.0, 0.0);
+        let forces = drag + potential + separated;
+        let moments = (potential_position - airflow.cg).cross(&potential)
+            + (self.centre - airflow.cg).cross(&(drag + separated));
+        (forces, moments)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+
+    use crate::{aero::AeroComponent, airflow::Airflow, fuselage::Fuselage};
+
+    #[test]
+    fn head_on_is_only_drag() {
+        let under_test = Fuselage::new(Vector3::new(-1.0, 0.0, 0.0), 8.0, 1.2);
+        let (forces, moments) = under_test.loads(&Airflow::uniform(Vector3::new(50.0, 0.0, 0.0)));
+        assert!(forces.x < 0.0);
+        assert_eq!((forces.y, forces.z), (0.0, 0.0));
+        assert!(moments.norm() < 1e-9);
+    }
+
+    #[test]
+    fn lifts_ahead_of_the_middle_and_is_unstable() {
+        let under_test = Fuselage::new(Vector3::zeros(), 8.0, 1.2);
+        let (forces, moments) = under_test.loads(&Airflow::uniform(Vector3::new(50.0, 0.0, 5.0)));
+        assert!(forces.z < 0.0);
+        // the lift ahead of the cg pitches the nose further up
+        assert!(moments.y > 0.0);
+        // and sideslip swings the nose further away from the wind
+        let (forces, moments) = under_test.loads(&Airflow::uniform(Vector3::new(50.0, 5.0, 0.0)));
+        assert!(forces.y < 0.0);
+        assert!(moments.z < 0.0);
+    }
+
+    #[test]
+    fn side_on_is_a_cylinder() {
+        let under_test = Fuselage::new(Vector3::zeros(), 8.0, 1.0);
+        let airflow = Airflow::uniform(Vector3::new(0.0, 0.0, 10.0));
+        let (forces, _) = under_test.loads(&airflow);
+        let area = 0.7 * 1.2 * 8.0 + 0.25 * std::f64::consts::PI * 0.15;
+        let expected = 0.5 * airflow.atmosphere.density * 100.0 * area;
+        assert!((-forces.z - expected).abs() < 1e-9);
+    }
+}
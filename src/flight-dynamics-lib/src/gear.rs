@@ -0,0 +1,550 @@
+use nalgebra::{UnitQuaternion, Vector3};
+
+use crate::{
+    cockpit::Cockpit, earth::EarthModel, integrator::RigidBodyState, state::STANDARD_GRAVITY,
+};
+
+/// below this speed friction fades out linearly instead of flipping direction every step
+const CREEP_SPEED: f64 = 1.0;
+/// a braked wheel going slower than this over the ground grabs hold of it, m/s
+const HOLD_SPEED: f64 = 0.1;
+
+pub struct Tyre {
+    pub radius: f64,
+    /// N/m
+    pub stiffness: f64,
+    /// N s/m
+    pub damping: f64,
+    /// fraction of the load that drags on a free rolling wheel
+    pub rolling_resistance: f64,
+    /// fraction of the load a fully braked wheel can take, rolling or held still
+    pub braking_friction: f64,
+    /// side force per unit load per radian of slip angle
+    pub cornering_stiffness: f64,
+    /// most grip the tyre has, as a fraction of the load
+    pub peak_friction: f64,
+    /// grip once it's skidding
+    pub sliding_friction: f64,
+}
+
+impl Tyre {
+    /// friction numbers are for rubber on dry tarmac
+    pub fn new(radius: f64, stiffness: f64, damping: f64) -> Tyre {
+        Tyre {
+            radius,
+            stiffness,
+            damping,
+            rolling_resistance: 0.02,
+            braking_friction: 0.5,
+            cornering_stiffness: 8.0,
+            peak_friction: 0.8,
+            sliding_friction: 0.6,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BrakeSide {
+    Left,
+    Right,
+}
+
+/// one oleo strut with a wheel on the end, the strut slides along the body z axis
+pub struct LandingGear {
+    pub name: String,
+    /// top of the strut, relative to the datum in body coordinates
+    pub attachment: Vector3<f64>,
+    /// how far the strut can compress before it bottoms out, m
+    pub stroke: f64,
+    /// N/m
+    pub spring: f64,
+    /// N s/m
+    pub damper: f64,
+    pub tyre: Tyre,
+    /// which toe brake works this wheel, if any
+    pub brake: Option<BrakeSide>,
+    /// wheel angle at full rudder, rad, 0 for a wheel that doesn't steer
+    pub max_steering_angle: f64,
+    /// follows the gear lever, fixed gear is always down
+    pub retractable: bool,
+    /// where a braked wheel stopped and is holding on to the ground, world coordinates
+    held_at: Option<Vector3<f64>>,
+}
+
+/// what one wheel is doing on the ground
+pub struct GearContact {
+    /// where the tyre touches, body coordinates relative to the cg
+    pub point: Vector3<f64>,
+    /// body coordinates
+    pub force: Vector3<f64>,
+    /// body coordinates, about the cg
+    pub moment: Vector3<f64>,
+    /// how far the strut has gone in, 0 is fully extended
+    pub strut_compression: f64,
+    pub tyre_compression: f64,
+    /// speed the contact point is moving into the ground, world coordinates, m/s
+    pub sink_rate: f64,
+    /// of the contact point, world coordinates, m/s
+    pub velocity: Vector3<f64>,
+    /// the tyre has run out of grip and is sliding
+    pub skidding: bool,
+}
+
+impl LandingGear {
+    pub fn new(
+        name: &str,
+        attachment: Vector3<f64>,
+        stroke: f64,
+        spring: f64,
+        damper: f64,
+        tyre: Tyre,
+    ) -> LandingGear {
+        LandingGear {
+            name: name.to_string(),
+            attachment,
+            stroke,
+            spring,
+            damper,
+            tyre,
+            brake: None,
+            max_steering_angle: 0.0,
+            retractable: false,
+            held_at: None,
+        }
+    }
+
+    pub fn with_brake(mut self, side: BrakeSide) -> LandingGear {
+        self.brake = Some(side);
+        self
+    }
+
+    pub fn with_steering(mut self, max_steering_angle: f64) -> LandingGear {
+        self.max_steering_angle = max_steering_angle;
+        self
+    }
+
+    pub fn retractable(mut self) -> LandingGear {
+        self.retractable = true;
+        self
+    }
+
+    /// retracted gear doesn't touch anything
+    pub fn is_extended(&self, controls: &Cockpit) -> bool {
+        !self.retractable || controls.gear_down
+    }
+
+    /// right is positive, left rudder turns the wheel left
+    pub fn steering_angle(&self, controls: &Cockpit) -> f64 {
+        -controls.yaw.clamp(-1.0, 1.0) * self.max_steering_angle
+    }
+
+    fn brake_input(&self, controls: &Cockpit) -> f64 {
+        match self.brake {
+            Some(BrakeSide::Left) => controls.brake_left,
+            Some(BrakeSide::Right) => controls.brake_right,
+            None => 0.0,
+        }
+        .clamp(0.0, 1.0)
+    }
+
+    /// bottom of the tyre with the strut fully extended, body coordinates relative to the datum
+    pub fn wheel_bottom(&self) -> Vector3<f64> {
+        self.attachment + Vector3::new(0.0, 0.0, self.stroke + self.tyre.radius)
+    }
+
+    /// relative to the cg in body coordinates, then where it is and how it's moving in the world
+    fn wheel_motion(
+        &self,
+        state: &RigidBodyState,
+        cg: &Vector3<f64>,
+    ) -> (Vector3<f64>, Vector3<f64>, Vector3<f64>) {
+        let rotation = state.transform.rotation;
+        let arm = self.wheel_bottom() - cg;
+        let wheel_world = state.transform.translation.vector + rotation * arm;
+        let point_velocity = state.velocity + rotation * state.angular_velocity.cross(&arm);
+        (arm, wheel_world, point_velocity)
+    }
+
+    /// call after each step, a braked wheel that has all but stopped grabs the ground where it is
+    /// and lets go when the brake comes off, the wheel leaves the ground or it gets dragged along
+    pub fn update_hold(
+        &mut self,
+        state: &RigidBodyState,
+        cg: &Vector3<f64>,
+        earth: &dyn EarthModel,
+        controls: &Cockpit,
+    ) {
+        let (_, wheel_world, point_velocity) = self.wheel_motion(state, cg);
+        let holding = self.brake_input(controls) > 0.0
+            && self.is_extended(controls)
+            && earth.height_above_ground(&wheel_world) < 0.0
+            && point_velocity.xy().norm() < HOLD_SPEED;
+        self.held_at = if holding {
+            self.held_at.or(Some(wheel_world))
+        } else {
+            None
+        };
+    }
+
+    /// the strut and tyre act as two springs in series, the strut takes its share until it bottoms out
+    /// and then the tyre takes the rest
+    /// friction is worked out along and across the wheel in the ground plane and kept inside the friction circle
+    /// dt is the physics step, grip never does more than stop the tyre sliding within it
+    pub fn contact(
+        &self,
+        state: &RigidBodyState,
+        cg: &Vector3<f64>,
+        earth: &dyn EarthModel,
+        controls: &Cockpit,
+        dt: f64,
+    ) -> Option<GearContact> {
+        if !self.is_extended(controls) {
+            return None;
+        }
+        let rotation = state.transform.rotation;
+        let (arm, wheel_world, point_velocity) = self.wheel_motion(state, cg);
+        let depth = -earth.height_above_ground(&wheel_world);
+        if depth <= 0.0 {
+            return None;
+        }
+
+        let sink_rate = point_velocity.z;
+
+        let tyre_share = self.tyre.stiffness / (self.spring + self.tyre.stiffness);
+        let (strut_compression, tyre_compression, damping) = if depth * tyre_share < self.stroke {
+            (depth * tyre_share, depth * (1.0 - tyre_share), self.damper)
+        } else {
+            (self.stroke, depth - self.stroke, self.tyre.damping)
+        };
+
+        // the ground can push but never pull
+        let normal = (self.tyre.stiffness * tyre_compression + damping * sink_rate).max(0.0);
+        let (friction, skidding) =
+            self.friction(state, &wheel_world, &point_velocity, normal, controls, dt);
+        let force = rotation.inverse() * (Vector3::new(0.0, 0.0, -normal) + friction);
+
+        // the wheel has been pushed up the strut, so the contact is higher than the fully extended wheel
+        let point = arm - Vector3::new(0.0, 0.0, strut_compression);
+
+        Some(GearContact {
+            point,
+            force,
+            moment: point.cross(&force),
+            strut_compression,
+            tyre_compression,
+            sink_rate,
+            velocity: point_velocity,
+            skidding,
+        })
+    }
+
+    /// world coordinates friction force on the tyre, and whether it's sliding
+    fn friction(
+        &self,
+        state: &RigidBodyState,
+        wheel_world: &Vector3<f64>,
+        point_velocity: &Vector3<f64>,
+        normal: f64,
+        controls: &Cockpit,
+        dt: f64,
+    ) -> (Vector3<f64>, bool) {
+        let wheel_heading = state.transform.rotation
+            * UnitQuaternion::from_euler_angles(0.0, 0.0, self.steering_angle(controls));
+        let mut forwards = wheel_heading * Vector3::x();
+        forwards.z = 0.0;
+        if forwards.norm() < 1e-6 {
+            // wheel pointing straight at the ground, nothing sensible to do
+            return (Vector3::zeros(), false);
+        }
+        let forwards = forwards.normalize();
+        let sideways = Vector3::z().cross(&forwards);
+
+        let rolling_speed = point_velocity.dot(&forwards);
+        let slip_speed = point_velocity.dot(&sideways);
+
+        // friction that would stop the tyre sliding by the end of the step, as a fraction of the load,
+        // taking the wheel to carry its load's worth of the plane
+        let step = dt.max(f64::MIN_POSITIVE);
+        let to_stop = |speed: f64| speed / (STANDARD_GRAVITY * step);
+
+        // a held wheel is pulled back to where it stopped as well, so a steady push can't creep it away
+        // either way the brake holds when that's inside what it can do, and slides otherwise
+        let stretch = self
+            .held_at
+            .map_or(0.0, |anchor| (wheel_world - anchor).dot(&forwards));
+        let braking = self.brake_input(controls) * self.tyre.braking_friction;
+        let longitudinal = -self.tyre.rolling_resistance
+            * (rolling_speed / CREEP_SPEED).clamp(-1.0, 1.0)
+            - to_stop(rolling_speed + stretch / step).clamp(-braking, braking);
+        let slip_angle = slip_speed.atan2(rolling_speed.abs().max(CREEP_SPEED));
+        let hold = to_stop(slip_speed).abs();
+        let lateral = (-self.tyre.cornering_stiffness * slip_angle).clamp(-hold, hold);
+
+        let mut coefficients = Vector3::new(longitudinal, lateral, 0.0);
+        let skidding = coefficients.norm() > self.tyre.peak_friction;
+        if skidding {
+            coefficients *= self.tyre.sliding_friction / coefficients.norm();
+        }
+
+        let friction = (forwards * coefficients.x + sideways * coefficients.y) * normal;
+        (friction, skidding)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::{Isometry3, Vector3};
+
+    use crate::{
+        cockpit::Cockpit,
+        earth::FlatEarth,
+        gear::{BrakeSide, LandingGear, Tyre},
+        integrator::RigidBodyState,
+    };
+
+    /// the physics step the plane runs at
+    const DT: f64 = 1.0 / 240.0;
+
+    fn make_gear() -> LandingGear {
+        LandingGear::new(
+            "test",
+            Vector3::new(1.0, 0.0, 0.5),
+            0.2,
+            50000.0,
+            2000.0,
+            Tyre::new(0.3, 200000.0, 500.0),
+        )
+        .with_brake(BrakeSide::Left)
+        .with_steering(0.5)
+    }
+
+    fn state_at_height(z: f64) -> RigidBodyState {
+        RigidBodyState::new(
+            Isometry3::new(Vector3::new(0.0, 0.0, z), Vector3::zeros()),
+            Vector3::zeros(),
+            Vector3::zeros(),
+        )
+    }
+
+    #[test]
+    fn no_force_in_the_air() {
+        let under_test = make_gear();
+        assert!(
+            under_test
+                .contact(
+                    &state_at_height(-1.01),
+                    &Vector3::zeros(),
+                    &FlatEarth::new(),
+                    &Cockpit::new(),
+                    DT,
+                )
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn springs_share_the_load() {
+        let under_test = make_gear();
+        // wheel bottom is 1.0 below the cg, so 0.1 into the ground
+        let contact = under_test
+            .contact(
+                &state_at_height(-0.9),
+                &Vector3::zeros(),
+                &FlatEarth::new(),
+                &Cockpit::new(),
+                DT,
+            )
+            .unwrap();
+        let series = 1.0 / (1.0 / 50000.0 + 1.0 / 200000.0);
+        assert!((-contact.force.z - series * 0.1).abs() < 1e-6);
+        assert!((contact.strut_compression - 0.08).abs() < 1e-9);
+        assert!((contact.tyre_compression - 0.02).abs() < 1e-9);
+        // wheel is ahead of the cg so pushing up lifts the nose
+        assert!(contact.moment.y > 0.0);
+    }
+
+    #[test]
+    fn bottomed_out_strut_loads_the_tyre() {
+        let under_test = make_gear();
+        let contact = under_test
+            .contact(
+                &state_at_height(-0.5),
+                &Vector3::zeros(),
+                &FlatEarth::new(),
+                &Cockpit::new(),
+                DT,
+            )
+            .unwrap();
+        assert_eq!(contact.strut_compression, 0.2);
+        assert!((contact.tyre_compression - 0.3).abs() < 1e-9);
+        assert!((-contact.force.z - 200000.0 * 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn damper_resists_sinking_but_never_pulls() {
+        let under_test = make_gear();
+        let mut state = state_at_height(-0.99);
+        state.velocity.z = 2.0;
+        let sinking = under_test
+            .contact(
+                &state,
+                &Vector3::zeros(),
+                &FlatEarth::new(),
+                &Cockpit::new(),
+                DT,
+            )
+            .unwrap();
+        assert!(-sinking.force.z > 4000.0);
+        assert_eq!(sinking.sink_rate, 2.0);
+
+        state.velocity.z = -2.0;
+        let rising = under_test
+            .contact(
+                &state,
+                &Vector3::zeros(),
+                &FlatEarth::new(),
+                &Cockpit::new(),
+                DT,
+            )
+            .unwrap();
+        assert_eq!(rising.force.z, 0.0);
+    }
+
+    fn rolling_at(velocity: Vector3<f64>, controls: &Cockpit) -> (Vector3<f64>, bool) {
+        let under_test = make_gear();
+        let mut state = state_at_height(-0.9);
+        state.velocity = velocity;
+        let contact = under_test
+            .contact(&state, &Vector3::zeros(), &FlatEarth::new(), controls, DT)
+            .unwrap();
+        (contact.force, contact.skidding)
+    }
+
+    #[test]
+    fn free_wheel_only_has_rolling_resistance() {
+        let (force, skidding) = rolling_at(Vector3::new(20.0, 0.0, 0.0), &Cockpit::new());
+        assert!((force.x / force.z - 0.02).abs() < 1e-9);
+        assert!(!skidding);
+    }
+
+    #[test]
+    fn brake_only_works_on_its_own_side() {
+        let mut controls = Cockpit::new();
+        controls.brake_right = 1.0;
+        let (unbraked, _) = rolling_at(Vector3::new(20.0, 0.0, 0.0), &controls);
+        controls.brake_left = 1.0;
+        let (braked, _) = rolling_at(Vector3::new(20.0, 0.0, 0.0), &controls);
+        assert!((braked.x / braked.z - 0.52).abs() < 1e-9);
+        assert!(braked.x < unbraked.x);
+    }
+
+    #[test]
+    fn side_slip_is_resisted_then_skids() {
+        let (gentle, skidding) = rolling_at(Vector3::new(20.0, 0.5, 0.0), &Cockpit::new());
+        assert!(gentle.y < 0.0);
+        assert!(!skidding);
+        let (sideways, skidding) = rolling_at(Vector3::new(5.0, 5.0, 0.0), &Cockpit::new());
+        assert!(skidding);
+        let grip = sideways.xy().norm() / -sideways.z;
+        assert!((grip - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn steering_turns_the_side_force() {
+        let mut controls = Cockpit::new();
+        controls.yaw = -0.1;
+        // right rudder, wheel turns right, straight ahead motion now slips to the left of the wheel
+        let (force, _) = rolling_at(Vector3::new(20.0, 0.0, 0.0), &controls);
+        assert!(force.y > 0.0);
+    }
+
+    #[test]
+    fn braked_wheel_grips_at_a_creep() {
+        let mut controls = Cockpit::new();
+        controls.brake_left = 1.0;
+        let (force, skidding) = rolling_at(Vector3::new(0.05, 0.0, 0.0), &controls);
+        assert!((force.x / force.z - 0.5).abs() < 0.01);
+        assert!(!skidding);
+    }
+
+    #[test]
+    fn braked_wheel_only_pushes_hard_enough_to_stop() {
+        let mut controls = Cockpit::new();
+        controls.brake_left = 1.0;
+        let (stopped, _) = rolling_at(Vector3::zeros(), &controls);
+        assert_eq!(stopped.x, 0.0);
+        // 1 mm/s is gone in one step with a lot less than the whole brake
+        let (creeping, _) = rolling_at(Vector3::new(0.001, 0.0, 0.0), &controls);
+        let expected = 0.001 / (9.80665 * DT) + 0.02 * 0.001;
+        assert!((creeping.x / creeping.z - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn side_grip_at_a_coarse_step_only_stops_the_slide() {
+        let mut state = state_at_height(-0.9);
+        state.velocity = Vector3::new(0.0, 0.01, 0.0);
+        let dt = 1.0 / 30.0;
+        let contact = make_gear()
+            .contact(
+                &state,
+                &Vector3::zeros(),
+                &FlatEarth::new(),
+                &Cockpit::new(),
+                dt,
+            )
+            .unwrap();
+        // less than the cornering stiffness would ask for, which would throw it back the other way
+        let expected = 0.01 / (9.80665 * dt);
+        assert!(expected < 8.0 * 0.01);
+        assert!((contact.force.y / contact.force.z - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn held_wheel_pulls_back_to_where_it_stopped() {
+        let mut under_test = make_gear();
+        let mut controls = Cockpit::new();
+        controls.brake_left = 1.0;
+        let mut state = state_at_height(-0.9);
+        under_test.update_hold(&state, &Vector3::zeros(), &FlatEarth::new(), &controls);
+        state.transform.translation.x = 1e-4;
+        let contact = under_test
+            .contact(&state, &Vector3::zeros(), &FlatEarth::new(), &controls, DT)
+            .unwrap();
+        assert!(contact.force.x < 0.0);
+        // too far to hold, so it's the full brake
+        state.transform.translation.x = 0.01;
+        let contact = under_test
+            .contact(&state, &Vector3::zeros(), &FlatEarth::new(), &controls, DT)
+            .unwrap();
+        assert!((contact.force.x / contact.force.z - 0.5).abs() < 1e-9);
+        // and it lets go with the brake off
+        controls.brake_left = 0.0;
+        under_test.update_hold(&state, &Vector3::zeros(), &FlatEarth::new(), &controls);
+        assert_eq!(under_test.held_at, None);
+    }
+
+    #[test]
+    fn retracted_gear_misses_the_ground() {
+        let fixed = make_gear();
+        let retractable = make_gear().retractable();
+        let mut controls = Cockpit::new();
+        controls.gear_down = false;
+        let state = state_at_height(-0.9);
+        assert!(
+            fixed
+                .contact(&state, &Vector3::zeros(), &FlatEarth::new(), &controls, DT)
+                .is_some()
+        );
+        assert!(
+            retractable
+                .contact(&state, &Vector3::zeros(), &FlatEarth::new(), &controls, DT)
+                .is_none()
+        );
+        controls.gear_down = true;
+        assert!(
+            retractable
+                .contact(&state, &Vector3::zeros(), &FlatEarth::new(), &controls, DT)
+                .is_some()
+        );
+    }
+}
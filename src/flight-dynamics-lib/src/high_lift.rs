@@ -0,0 +1,352 @@
+use crate::{cockpit::Cockpit, control::plain_flap};
+
+/// lift a fully raised spoiler takes away from the attached flow
+const SPOILER_LIFT_DUMP: f64 = 0.7;
+/// profile drag of a fully raised spoiler
+const SPOILER_DRAG: f64 = 0.06;
+/// profile drag of the gap an extended slat opens up
+const SLAT_DRAG: f64 = 0.004;
+
+/// something that runs from one position to another at a fixed speed, like a flap motor
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Actuator {
+    pub position: f64,
+    pub target: f64,
+    /// units per second
+    pub rate: f64,
+}
+
+impl Actuator {
+    pub fn new(rate: f64) -> Actuator {
+        Actuator {
+            position: 0.0,
+            target: 0.0,
+            rate,
+        }
+    }
+
+    /// covers the travel in the travel time, no time at all or less makes it instant
+    pub fn with_travel_time(travel: f64, travel_time: f64) -> Actuator {
+        if travel_time > 0.0 {
+            Actuator::new(travel.abs() / travel_time)
+        } else {
+            Actuator::new(f64::INFINITY)
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        if self.rate == f64::INFINITY {
+            self.position = self.target;
+            return;
+        }
+        let step = self.rate * dt;
+        self.position += (self.target - self.position).clamp(-step, step);
+    }
+}
+
+/// how a high lift device changes a wing's section
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Increments {
+    /// moves the lift curve, rad
+    pub alpha_shift: f64,
+    /// added to cl_max, so the stall comes later
+    pub cl_max: f64,
+    pub drag: f64,
+    pub pitching_moment: f64,
+    /// multiplies the attached lift
+    pub lift_scale: f64,
+}
+
+impl Increments {
+    pub fn none() -> Increments {
+        Increments {
+            alpha_shift: 0.0,
+            cl_max: 0.0,
+            drag: 0.0,
+            pitching_moment: 0.0,
+            lift_scale: 1.0,
+        }
+    }
+
+    pub fn combine(self, other: Increments) -> Increments {
+        Increments {
+            alpha_shift: self.alpha_shift + other.alpha_shift,
+            cl_max: self.cl_max + other.cl_max,
+            drag: self.drag + other.drag,
+            pitching_moment: self.pitching_moment + other.pitching_moment,
+            lift_scale: self.lift_scale * other.lift_scale,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlapKind {
+    /// the back of the wing hinges down
+    Plain,
+    /// only the underside hinges down, lots of drag
+    Split,
+    /// slides back and down, the wing gets bigger as well as more cambered
+    Fowler,
+}
+
+impl FlapKind {
+    /// how much of the extra lift is still there at the stall, the rest comes off the stall angle
+    fn cl_max_fraction(self) -> f64 {
+        match self {
+            FlapKind::Plain => 0.6,
+            FlapKind::Split => 0.7,
+            FlapKind::Fowler => 0.8,
+        }
+    }
+
+    /// compared with the plain flap's thin aerofoil drag
+    fn drag_factor(self) -> f64 {
+        match self {
+            FlapKind::Plain => 1.0,
+            FlapKind::Split => 2.0,
+            FlapKind::Fowler => 0.6,
+        }
+    }
+}
+
+/// trailing edge flaps with a lever that clicks between stages
+pub struct Flap {
+    pub kind: FlapKind,
+    /// how much of the chord is flap
+    pub chord_fraction: f64,
+    /// how much of the panel's span has flap along it
+    pub span_fraction: f64,
+    /// deflection at each stage of the lever, the first is normally up, rad
+    pub stages: Vec<f64>,
+    deflection: Actuator,
+}
+
+impl Flap {
+    /// travel time is how long the motor takes from up to the last stage, s
+    pub fn new(
+        kind: FlapKind,
+        chord_fraction: f64,
+        span_fraction: f64,
+        stages: Vec<f64>,
+        travel_time: f64,
+    ) -> Flap {
+        let full = stages.iter().copied().fold(0.0, f64::max);
+        Flap {
+            kind,
+            chord_fraction,
+            span_fraction,
+            stages,
+            deflection: Actuator::with_travel_time(full, travel_time),
+        }
+    }
+
+    /// rad, trailing edge down
+    pub fn deflection(&self) -> f64 {
+        self.deflection.position
+    }
+
+    /// the lever past the last stage just stays at the last stage
+    pub fn update_controls(&mut self, controls: &Cockpit) {
+        let stage = controls.flaps.min(self.stages.len().saturating_sub(1));
+        self.deflection.target = self.stages.get(stage).copied().unwrap_or(0.0);
+    }
+
+    pub fn move_actuators(&mut self, dt: f64) {
+        self.deflection.update(dt);
+    }
+
+    /// dcl_dalpha is the wing's, for turning the shift in alpha into lift
+    pub fn increments(&self, dcl_dalpha: f64) -> Increments {
+        let deflection = self.deflection();
+        let effect = plain_flap(self.chord_fraction, deflection);
+        let full = self.stages.iter().copied().fold(0.0, f64::max);
+        // a fowler flap slides its own chord back by the time it's all the way out
+        let extension = match self.kind {
+            FlapKind::Fowler if full > 0.0 => self.chord_fraction * deflection / full,
+            _ => 0.0,
+        };
+        let alpha_shift = self.span_fraction * effect.alpha_shift;
+        Increments {
+            alpha_shift,
+            cl_max: self.kind.cl_max_fraction() * dcl_dalpha * alpha_shift,
+            drag: self.span_fraction * self.kind.drag_factor() * effect.drag,
+            pitching_moment: self.span_fraction * effect.pitching_moment * (1.0 + extension),
+            lift_scale: 1.0 + self.span_fraction * extension,
+        }
+    }
+}
+
+/// leading edge slats, they let the wing get to a higher alpha before it stalls
+pub struct Slat {
+    /// how much later the wing stalls with them out, rad
+    pub stall_delay: f64,
+    extension: Actuator,
+}
+
+impl Slat {
+    pub fn new(stall_delay: f64, travel_time: f64) -> Slat {
+        Slat {
+            stall_delay,
+            extension: Actuator::with_travel_time(1.0, travel_time),
+        }
+    }
+
+    /// 0 in to 1 out
+    pub fn extension(&self) -> f64 {
+        self.extension.position
+    }
+
+    pub fn update_controls(&mut self, controls: &Cockpit) {
+        self.extension.target = if controls.slats { 1.0 } else { 0.0 };
+    }
+
+    pub fn move_actuators(&mut self, dt: f64) {
+        self.extension.update(dt);
+    }
+
+    pub fn increments(&self, dcl_dalpha: f64) -> Increments {
+        Increments {
+            cl_max: dcl_dalpha * self.stall_delay * self.extension(),
+            drag: SLAT_DRAG * self.extension(),
+            ..Increments::none()
+        }
+    }
+}
+
+/// plates that pop up out of the top of the wing, spoil the lift and add drag
+/// used as speedbrakes in the air and lift dumpers on the ground
+pub struct Spoiler {
+    extension: Actuator,
+}
+
+impl Spoiler {
+    pub fn new(travel_time: f64) -> Spoiler {
+        Spoiler {
+            extension: Actuator::with_travel_time(1.0, travel_time),
+        }
+    }
+
+    /// 0 down to 1 fully up
+    pub fn extension(&self) -> f64 {
+        self.extension.position
+    }
+
+    pub fn update_controls(&mut self, controls: &Cockpit) {
+        self.extension.target = controls.spoilers.clamp(0.0, 1.0);
+    }
+
+    pub fn move_actuators(&mut self, dt: f64) {
+        self.extension.update(dt);
+    }
+
+    pub fn increments(&self) -> Increments {
+        Increments {
+            drag: SPOILER_DRAG * self.extension(),
+            lift_scale: 1.0 - SPOILER_LIFT_DUMP * self.extension(),
+            ..Increments::none()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cockpit::Cockpit,
+        high_lift::{Actuator, Flap, FlapKind, Slat, Spoiler},
+    };
+
+    fn stages() -> Vec<f64> {
+        [0.0_f64, 10.0, 20.0, 30.0]
+            .iter()
+            .map(|stage| stage.to_radians())
+            .collect()
+    }
+
+    #[test]
+    fn actuators_take_their_time() {
+        let mut under_test = Actuator::new(0.5);
+        under_test.target = 1.0;
+        under_test.update(1.0);
+        assert_eq!(under_test.position, 0.5);
+        under_test.update(10.0);
+        assert_eq!(under_test.position, 1.0);
+        under_test.target = 0.8;
+        under_test.update(0.1);
+        assert!((under_test.position - 0.95).abs() < 1e-12);
+    }
+
+    #[test]
+    fn no_travel_time_is_instant() {
+        let mut under_test = Actuator::with_travel_time(1.0, 0.0);
+        under_test.target = 1.0;
+        under_test.update(0.0);
+        assert_eq!(under_test.position, 1.0);
+        let down = 20.0_f64.to_radians();
+        let mut flap = Flap::new(FlapKind::Plain, 0.25, 0.6, vec![0.0, down], 0.0);
+        let mut controls = Cockpit::new();
+        controls.flaps = 1;
+        flap.update_controls(&controls);
+        flap.move_actuators(0.01);
+        assert!((flap.deflection() - down).abs() < 1e-12);
+        let mut spoiler = Spoiler::new(-1.0);
+        controls.spoilers = 1.0;
+        spoiler.update_controls(&controls);
+        spoiler.move_actuators(0.01);
+        assert_eq!(spoiler.extension(), 1.0);
+    }
+
+    #[test]
+    fn flaps_run_out_to_the_stage_selected() {
+        let mut under_test = Flap::new(FlapKind::Plain, 0.25, 0.6, stages(), 6.0);
+        let mut controls = Cockpit::new();
+        controls.flaps = 2;
+        under_test.update_controls(&controls);
+        under_test.move_actuators(2.0);
+        assert!((under_test.deflection() - 10.0_f64.to_radians()).abs() < 1e-12);
+        under_test.move_actuators(10.0);
+        assert!((under_test.deflection() - 20.0_f64.to_radians()).abs() < 1e-12);
+        // past the last stage
+        controls.flaps = 9;
+        under_test.update_controls(&controls);
+        under_test.move_actuators(10.0);
+        assert!((under_test.deflection() - 30.0_f64.to_radians()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn flap_kinds_trade_lift_for_drag() {
+        let deployed = |kind: FlapKind| {
+            let mut flap = Flap::new(kind, 0.3, 1.0, stages(), 1.0);
+            let mut controls = Cockpit::new();
+            controls.flaps = 3;
+            flap.update_controls(&controls);
+            flap.move_actuators(1.0);
+            flap.increments(5.0)
+        };
+        let plain = deployed(FlapKind::Plain);
+        let split = deployed(FlapKind::Split);
+        let fowler = deployed(FlapKind::Fowler);
+        assert!(plain.alpha_shift > 0.0 && plain.pitching_moment < 0.0);
+        // extra lift at the stall, but less than the extra lift before it
+        assert!(plain.cl_max > 0.0 && plain.cl_max < 5.0 * plain.alpha_shift);
+        assert!(split.drag > plain.drag);
+        assert!(fowler.lift_scale > 1.0 && fowler.drag < plain.drag);
+    }
+
+    #[test]
+    fn slats_delay_the_stall_and_spoilers_dump_lift() {
+        let mut controls = Cockpit::new();
+        controls.slats = true;
+        controls.spoilers = 1.0;
+        let mut slat = Slat::new(10.0_f64.to_radians(), 2.0);
+        let mut spoiler = Spoiler::new(1.0);
+        slat.update_controls(&controls);
+        spoiler.update_controls(&controls);
+        slat.move_actuators(1.0);
+        spoiler.move_actuators(1.0);
+        assert_eq!(slat.extension(), 0.5);
+        assert!(slat.increments(5.0).cl_max > 0.4);
+        assert_eq!(slat.increments(5.0).alpha_shift, 0.0);
+        assert!(spoiler.increments().lift_scale < 0.5);
+        assert!(spoiler.increments().drag > 0.0);
+    }
+}
@@ -0,0 +1,457 @@
+use std::ops::{Add, Mul};
+
+use nalgebra::{Isometry3, UnitQuaternion, Vector3};
+
+/// the part of the plane that gets integrated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RigidBodyState {
+    pub transform: Isometry3<f64>,
+    /// world coordinates, m/s
+    pub velocity: Vector3<f64>,
+    /// body axis rates p, q, r in rad/s
+    pub angular_velocity: Vector3<f64>,
+}
+
+/// rates of change of a RigidBodyState
+/// linear terms are in world coordinates, angular terms are body axis
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StateDerivative {
+    pub velocity: Vector3<f64>,
+    pub angular_velocity: Vector3<f64>,
+    pub acceleration: Vector3<f64>,
+    pub angular_acceleration: Vector3<f64>,
+}
+
+impl RigidBodyState {
+    pub fn new(
+        transform: Isometry3<f64>,
+        velocity: Vector3<f64>,
+        angular_velocity: Vector3<f64>,
+    ) -> RigidBodyState {
+        RigidBodyState {
+            transform,
+            velocity,
+            angular_velocity,
+        }
+    }
+
+    /// move every part of the state along the derivative for dt
+    /// the attitude follows q' = 0.5 * q * (0, w), which for a body rate held over the step is
+    /// q * exp(w dt / 2), then gets renormalised so rounding can't build up
+    pub fn advanced(&self, derivative: &StateDerivative, dt: f64) -> RigidBodyState {
+        let mut transform = self.transform;
+        transform.translation.vector += derivative.velocity * dt;
+        let mut rotation =
+            transform.rotation * UnitQuaternion::from_scaled_axis(derivative.angular_velocity * dt);
+        rotation.renormalize();
+        transform.rotation = rotation;
+
+        RigidBodyState {
+            transform,
+            velocity: self.velocity + derivative.acceleration * dt,
+            angular_velocity: self.angular_velocity + derivative.angular_acceleration * dt,
+        }
+    }
+}
+
+impl StateDerivative {
+    pub fn zero() -> StateDerivative {
+        StateDerivative {
+            velocity: Vector3::zeros(),
+            angular_velocity: Vector3::zeros(),
+            acceleration: Vector3::zeros(),
+            angular_acceleration: Vector3::zeros(),
+        }
+    }
+}
+
+impl Add for StateDerivative {
+    type Output = StateDerivative;
+
+    fn add(self, other: StateDerivative) -> StateDerivative {
+        StateDerivative {
+            velocity: self.velocity + other.velocity,
+            angular_velocity: self.angular_velocity + other.angular_velocity,
+            acceleration: self.acceleration + other.acceleration,
+            angular_acceleration: self.angular_acceleration + other.angular_acceleration,
+        }
+    }
+}
+
+impl Mul<f64> for StateDerivative {
+    type Output = StateDerivative;
+
+    fn mul(self, scale: f64) -> StateDerivative {
+        StateDerivative {
+            velocity: self.velocity * scale,
+            angular_velocity: self.angular_velocity * scale,
+            acceleration: self.acceleration * scale,
+            angular_acceleration: self.angular_acceleration * scale,
+        }
+    }
+}
+
+/// something that can move a state forward in time given a way to work out its derivative
+pub trait Integrator: Send + Sync {
+    fn step(
+        &self,
+        state: &RigidBodyState,
+        dt: f64,
+        derivative: &dyn Fn(&RigidBodyState) -> StateDerivative,
+    ) -> RigidBodyState;
+}
+
+/// first order, cheapest, gains energy
+pub struct ForwardEuler;
+
+impl Integrator for ForwardEuler {
+    fn step(
+        &self,
+        state: &RigidBodyState,
+        dt: f64,
+        derivative: &dyn Fn(&RigidBodyState) -> StateDerivative,
+    ) -> RigidBodyState {
+        state.advanced(&derivative(state), dt)
+    }
+}
+
+/// first order, but the pose is moved with the updated rates which keeps orbits and oscillations bounded
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn step(
+        &self,
+        state: &RigidBodyState,
+        dt: f64,
+        derivative: &dyn Fn(&RigidBodyState) -> StateDerivative,
+    ) -> RigidBodyState {
+        let mut rates = derivative(state);
+        rates.velocity += rates.acceleration * dt;
+        rates.angular_velocity += rates.angular_acceleration * dt;
+        state.advanced(&rates, dt)
+    }
+}
+
+/// classic fourth order runge kutta, four derivative evaluations per step
+pub struct RungeKutta4;
+
+impl Integrator for RungeKutta4 {
+    fn step(
+        &self,
+        state: &RigidBodyState,
+        dt: f64,
+        derivative: &dyn Fn(&RigidBodyState) -> StateDerivative,
+    ) -> RigidBodyState {
+        let k1 = derivative(state);
+        let k2 = derivative(&state.advanced(&k1, dt / 2.0));
+        let k3 = derivative(&state.advanced(&k2, dt / 2.0));
+        let k4 = derivative(&state.advanced(&k3, dt));
+        let average = (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (1.0 / 6.0);
+        state.advanced(&average, dt)
+    }
+}
+
+/// dormand prince 5(4), splits the step up until the error estimate is inside the tolerances
+pub struct AdaptiveRk45 {
+    pub absolute_tolerance: f64,
+    pub relative_tolerance: f64,
+    /// stop splitting below this, so a nasty derivative can't hang the sim
+    pub min_step: f64,
+}
+
+impl AdaptiveRk45 {
+    pub fn new(absolute_tolerance: f64, relative_tolerance: f64) -> AdaptiveRk45 {
+        AdaptiveRk45 {
+            absolute_tolerance,
+            relative_tolerance,
+            min_step: 1e-6,
+        }
+    }
+
+    const A: [[f64; 6]; 7] = [
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+        [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+        [
+            19372.0 / 6561.0,
+            -25360.0 / 2187.0,
+            64448.0 / 6561.0,
+            -212.0 / 729.0,
+            0.0,
+            0.0,
+        ],
+        [
+            9017.0 / 3168.0,
+            -355.0 / 33.0,
+            46732.0 / 5247.0,
+            49.0 / 176.0,
+            -5103.0 / 18656.0,
+            0.0,
+        ],
+        [
+            35.0 / 384.0,
+            0.0,
+            500.0 / 1113.0,
+            125.0 / 192.0,
+            -2187.0 / 6784.0,
+            11.0 / 84.0,
+        ],
+    ];
+    const B5: [f64; 7] = [
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+        0.0,
+    ];
+    const B4: [f64; 7] = [
+        5179.0 / 57600.0,
+        0.0,
+        7571.0 / 16695.0,
+        393.0 / 640.0,
+        -92097.0 / 339200.0,
+        187.0 / 2100.0,
+        1.0 / 40.0,
+    ];
+
+    fn weighted(weights: &[f64], stages: &[StateDerivative]) -> StateDerivative {
+        weights
+            .iter()
+            .zip(stages)
+            .fold(StateDerivative::zero(), |total, (weight, stage)| {
+                total + *stage * *weight
+            })
+    }
+
+    /// one dormand prince step, gives the fifth order answer and the scaled error (<= 1 is acceptable)
+    fn attempt(
+        &self,
+        state: &RigidBodyState,
+        h: f64,
+        derivative: &dyn Fn(&RigidBodyState) -> StateDerivative,
+    ) -> (RigidBodyState, f64) {
+        let mut stages = Vec::with_capacity(7);
+        for (i, row) in Self::A.iter().enumerate() {
+            let stage_state = state.advanced(&Self::weighted(&row[..i], &stages), h);
+            stages.push(derivative(&stage_state));
+        }
+
+        let fifth_order = state.advanced(&Self::weighted(&Self::B5, &stages), h);
+        let difference: Vec<f64> = Self::B5
+            .iter()
+            .zip(Self::B4)
+            .map(|(b5, b4)| b5 - b4)
+            .collect();
+        let error = Self::weighted(&difference, &stages) * h;
+
+        let scale = |size: f64| self.absolute_tolerance + self.relative_tolerance * size;
+        let error_norm = [
+            error.velocity.norm() / scale(fifth_order.transform.translation.vector.norm()),
+            error.angular_velocity.norm() / scale(fifth_order.transform.rotation.angle()),
+            error.acceleration.norm() / scale(fifth_order.velocity.norm()),
+            error.angular_acceleration.norm() / scale(fifth_order.angular_velocity.norm()),
+        ]
+        .into_iter()
+        .fold(0.0, f64::max);
+
+        (fifth_order, error_norm)
+    }
+}
+
+impl Integrator for AdaptiveRk45 {
+    fn step(
+        &self,
+        state: &RigidBodyState,
+        dt: f64,
+        derivative: &dyn Fn(&RigidBodyState) -> StateDerivative,
+    ) -> RigidBodyState {
+        let mut state = *state;
+        let mut elapsed = 0.0;
+        let mut h = dt;
+        while elapsed < dt {
+            h = h.min(dt - elapsed);
+            let (next, error) = self.attempt(&state, h, derivative);
+            if error <= 1.0 || h <= self.min_step {
+                state = next;
+                elapsed += h;
+            }
+            // usual safety factor, and don't let the step change too violently
+            let factor = if error > 0.0 {
+                0.9 * error.powf(-0.2)
+            } else {
+                5.0
+            };
+            h = (h * factor.clamp(0.2, 5.0)).max(self.min_step);
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use nalgebra::{Isometry3, UnitQuaternion, Vector3};
+
+    use crate::integrator::{
+        AdaptiveRk45, ForwardEuler, Integrator, RigidBodyState, RungeKutta4, SemiImplicitEuler,
+        StateDerivative,
+    };
+
+    const GRAVITY: f64 = 9.81;
+
+    fn ballistic(state: &RigidBodyState) -> StateDerivative {
+        StateDerivative {
+            velocity: state.velocity,
+            angular_velocity: state.angular_velocity,
+            acceleration: Vector3::new(0.0, 0.0, GRAVITY),
+            angular_acceleration: Vector3::zeros(),
+        }
+    }
+
+    /// unit mass on a unit spring along x, so x = cos(t)
+    fn spring(state: &RigidBodyState) -> StateDerivative {
+        StateDerivative {
+            velocity: state.velocity,
+            angular_velocity: state.angular_velocity,
+            acceleration: -state.transform.translation.vector,
+            angular_acceleration: Vector3::zeros(),
+        }
+    }
+
+    /// rolling with a steady angular acceleration, 0.5 rad/s^2
+    fn roll_acceleration(state: &RigidBodyState) -> StateDerivative {
+        StateDerivative {
+            velocity: state.velocity,
+            angular_velocity: state.angular_velocity,
+            acceleration: Vector3::zeros(),
+            angular_acceleration: Vector3::new(0.5, 0.0, 0.0),
+        }
+    }
+
+    fn run(
+        integrator: &dyn Integrator,
+        mut state: RigidBodyState,
+        dt: f64,
+        end: f64,
+        derivative: &dyn Fn(&RigidBodyState) -> StateDerivative,
+    ) -> RigidBodyState {
+        let steps = (end / dt).round() as usize;
+        for _ in 0..steps {
+            state = integrator.step(&state, dt, derivative);
+        }
+        state
+    }
+
+    fn ballistic_error(integrator: &dyn Integrator, dt: f64) -> f64 {
+        let start_velocity = Vector3::new(20.0, 0.0, -15.0);
+        let start = RigidBodyState::new(Isometry3::identity(), start_velocity, Vector3::zeros());
+        let end = 2.0;
+        let state = run(integrator, start, dt, end, &ballistic);
+        let exact = start_velocity * end + Vector3::new(0.0, 0.0, 0.5 * GRAVITY * end * end);
+        (state.transform.translation.vector - exact).norm()
+    }
+
+    fn spring_error(integrator: &dyn Integrator, dt: f64) -> f64 {
+        let start = RigidBodyState::new(
+            Isometry3::new(Vector3::new(1.0, 0.0, 0.0), Vector3::zeros()),
+            Vector3::zeros(),
+            Vector3::zeros(),
+        );
+        let end = 2.0;
+        let state = run(integrator, start, dt, end, &spring);
+        (state.transform.translation.x - end.cos()).abs()
+    }
+
+    fn roll_error(integrator: &dyn Integrator, dt: f64) -> f64 {
+        let start = RigidBodyState::new(Isometry3::identity(), Vector3::zeros(), Vector3::zeros());
+        let end = 2.0;
+        let state = run(integrator, start, dt, end, &roll_acceleration);
+        let (roll, _, _) = state.transform.rotation.euler_angles();
+        (roll - 0.25 * end * end).abs()
+    }
+
+    #[test]
+    fn euler_ballistic_error_is_first_order() {
+        for integrator in [
+            &ForwardEuler as &dyn Integrator,
+            &SemiImplicitEuler as &dyn Integrator,
+        ] {
+            let coarse = ballistic_error(integrator, 0.1);
+            let fine = ballistic_error(integrator, 0.05);
+            assert!(coarse > 1e-2);
+            assert!((coarse / fine - 2.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn rk_ballistic_is_exact() {
+        assert!(ballistic_error(&RungeKutta4, 0.1) < 1e-9);
+        assert!(ballistic_error(&AdaptiveRk45::new(1e-9, 1e-9), 0.1) < 1e-9);
+    }
+
+    #[test]
+    fn rk4_spring_error_is_fourth_order() {
+        let coarse = spring_error(&RungeKutta4, 0.1);
+        let fine = spring_error(&RungeKutta4, 0.05);
+        assert!(coarse < 1e-5);
+        assert!((coarse / fine - 16.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn euler_spring_error_grows_with_dt() {
+        let errors: Vec<f64> = [0.1, 0.05, 0.025]
+            .into_iter()
+            .map(|dt| spring_error(&ForwardEuler, dt))
+            .collect();
+        assert!(errors[0] > errors[1] && errors[1] > errors[2]);
+        assert!(errors[2] > spring_error(&RungeKutta4, 0.1));
+    }
+
+    #[test]
+    fn adaptive_meets_tolerance_with_big_steps() {
+        let under_test = AdaptiveRk45::new(1e-8, 1e-8);
+        let adaptive = spring_error(&under_test, 0.5);
+        assert!(adaptive < 1e-6);
+        assert!(spring_error(&ForwardEuler, 0.5) > 1000.0 * adaptive);
+    }
+
+    #[test]
+    fn constant_rate_roll_is_exact_for_all() {
+        let start = RigidBodyState::new(
+            Isometry3::identity(),
+            Vector3::zeros(),
+            Vector3::new(PI / 4.0, 0.0, 0.0),
+        );
+        let integrators: [&dyn Integrator; 4] = [
+            &ForwardEuler,
+            &SemiImplicitEuler,
+            &RungeKutta4,
+            &AdaptiveRk45::new(1e-9, 1e-9),
+        ];
+        for integrator in integrators {
+            let state = run(integrator, start, 0.1, 2.0, &ballistic_without_gravity);
+            let expected = UnitQuaternion::from_euler_angles(PI / 2.0, 0.0, 0.0);
+            assert!(state.transform.rotation.angle_to(&expected) < 1e-9);
+        }
+    }
+
+    fn ballistic_without_gravity(state: &RigidBodyState) -> StateDerivative {
+        StateDerivative {
+            acceleration: Vector3::zeros(),
+            ..ballistic(state)
+        }
+    }
+
+    #[test]
+    fn accelerating_roll_error_shrinks_with_order() {
+        let euler_coarse = roll_error(&ForwardEuler, 0.1);
+        let euler_fine = roll_error(&ForwardEuler, 0.05);
+        assert!((euler_coarse / euler_fine - 2.0).abs() < 1e-6);
+        assert!(roll_error(&SemiImplicitEuler, 0.05) < euler_coarse);
+        assert!(roll_error(&RungeKutta4, 0.1) < 1e-9);
+    }
+}
@@ -1,4 +1,22 @@
+pub mod aero;
+pub mod airflow;
+pub mod airfoil;
+pub mod atmosphere;
 pub mod cockpit;
+pub mod control;
+pub mod damage;
+pub mod downwash;
+pub mod earth;
 pub mod engine;
+pub mod fuselage;
+pub mod gear;
+pub mod high_lift;
+pub mod integrator;
+pub mod mass;
 pub mod plane;
+pub mod planform;
+pub mod side;
+pub mod state;
+pub mod timestep;
+pub mod vlm;
 pub mod wing;
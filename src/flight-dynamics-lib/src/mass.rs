@@ -0,0 +1,244 @@
+use std::fmt;
+
+use nalgebra::{Matrix3, Vector3};
+
+#[derive(Debug, PartialEq)]
+pub enum MassError {
+    /// nothing to weigh, or it all adds up to nothing
+    NoMass,
+    /// a component with a negative, NaN or infinite mass, or somewhere that isn't a place
+    BadComponent { name: String },
+    /// everything on one line or in one spot, so there's some axis it turns about for free
+    SingularInertia,
+}
+
+impl fmt::Display for MassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MassError::NoMass => write!(f, "the masses add up to nothing"),
+            MassError::BadComponent { name } => write!(f, "{name} has a bad mass or position"),
+            MassError::SingularInertia => write!(f, "the inertia can't be inverted"),
+        }
+    }
+}
+
+impl std::error::Error for MassError {}
+
+/// a lump of mass carried by the plane, position is relative to the datum in body coordinates
+pub struct MassComponent {
+    pub name: String,
+    pub mass: f64,
+    pub position: Vector3<f64>,
+    /// inertia about the component's own centre, zero for a point mass
+    pub inertia: Matrix3<f64>,
+}
+
+impl MassComponent {
+    pub fn point(name: &str, mass: f64, position: Vector3<f64>) -> MassComponent {
+        MassComponent {
+            name: name.to_string(),
+            mass,
+            position,
+            inertia: Matrix3::zeros(),
+        }
+    }
+
+    /// for things that are too big to be a point, like the fuselage
+    pub fn with_inertia(mut self, inertia: Matrix3<f64>) -> MassComponent {
+        self.inertia = inertia;
+        self
+    }
+
+    /// finite everything and no negative mass
+    fn is_sensible(&self) -> bool {
+        self.mass.is_finite()
+            && self.mass >= 0.0
+            && self.position.iter().all(|x| x.is_finite())
+            && self.inertia.iter().all(|x| x.is_finite())
+    }
+}
+
+/// totals for the whole plane
+/// inertia is the full tensor about the cg in body axes, so the off diagonal terms are minus the products of inertia
+pub struct MassProperties {
+    pub mass: f64,
+    pub cg: Vector3<f64>,
+    pub inertia: Matrix3<f64>,
+    /// worked out once here rather than every time the moments are turned into accelerations
+    pub inertia_inverse: Matrix3<f64>,
+}
+
+impl MassProperties {
+    /// the total has to be more than nothing, or there's no cg to find
+    /// and the inertia has to resist turning about every axis
+    pub fn from_components(components: &[MassComponent]) -> Result<MassProperties, MassError> {
+        if let Some(bad) = components.iter().find(|component| !component.is_sensible()) {
+            return Err(MassError::BadComponent {
+                name: bad.name.clone(),
+            });
+        }
+        let mass: f64 = components.iter().map(|component| component.mass).sum();
+        if mass <= 0.0 {
+            return Err(MassError::NoMass);
+        }
+        let cg = components
+            .iter()
+            .map(|component| component.position * component.mass)
+            .sum::<Vector3<f64>>()
+            / mass;
+
+        let inertia: Matrix3<f64> = components
+            .iter()
+            .map(|component| {
+                // parallel axis theorem, moving each component's inertia to the cg
+                let offset = component.position - cg;
+                component.inertia
+                    + component.mass
+                        * (Matrix3::identity() * offset.norm_squared()
+                            - offset * offset.transpose())
+            })
+            .sum();
+
+        // only a positive definite tensor has a cholesky factor
+        let inertia_inverse = inertia
+            .cholesky()
+            .ok_or(MassError::SingularInertia)?
+            .inverse();
+
+        Ok(MassProperties {
+            mass,
+            cg,
+            inertia,
+            inertia_inverse,
+        })
+    }
+
+    pub fn product_of_inertia_xy(&self) -> f64 {
+        -self.inertia[(0, 1)]
+    }
+
+    pub fn product_of_inertia_xz(&self) -> f64 {
+        -self.inertia[(0, 2)]
+    }
+
+    pub fn product_of_inertia_yz(&self) -> f64 {
+        -self.inertia[(1, 2)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::{Matrix3, Vector3};
+
+    use crate::mass::{MassComponent, MassError, MassProperties};
+
+    /// something solid at the cg so the point masses don't leave a free axis
+    fn body(position: Vector3<f64>) -> MassComponent {
+        MassComponent::point("body", 1.0, position).with_inertia(Matrix3::identity())
+    }
+
+    #[test]
+    fn cg_between_equal_masses() {
+        let under_test = MassProperties::from_components(&[
+            MassComponent::point("left", 10.0, Vector3::new(1.0, -2.0, 0.0)),
+            MassComponent::point("right", 10.0, Vector3::new(1.0, 2.0, 0.0)),
+            body(Vector3::new(1.0, 0.0, 0.0)),
+        ])
+        .unwrap();
+        assert_eq!(under_test.mass, 21.0);
+        assert_eq!(under_test.cg, Vector3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn masses_on_wingtips_give_roll_and_yaw_inertia() {
+        let under_test = MassProperties::from_components(&[
+            MassComponent::point("left", 10.0, Vector3::new(0.0, -2.0, 0.0)),
+            MassComponent::point("right", 10.0, Vector3::new(0.0, 2.0, 0.0)),
+            body(Vector3::zeros()),
+        ])
+        .unwrap();
+        assert_eq!(under_test.inertia[(0, 0)], 81.0);
+        assert_eq!(under_test.inertia[(1, 1)], 1.0);
+        assert_eq!(under_test.inertia[(2, 2)], 81.0);
+        assert_eq!(under_test.product_of_inertia_xy(), 0.0);
+    }
+
+    #[test]
+    fn nose_low_masses_give_xz_product() {
+        let under_test = MassProperties::from_components(&[
+            MassComponent::point("engine", 10.0, Vector3::new(1.0, 0.0, 1.0)),
+            MassComponent::point("tail", 10.0, Vector3::new(-1.0, 0.0, -1.0)),
+            body(Vector3::zeros()),
+        ])
+        .unwrap();
+        assert_eq!(under_test.cg, Vector3::zeros());
+        assert_eq!(under_test.product_of_inertia_xz(), 20.0);
+        assert_eq!(under_test.inertia[(2, 0)], -20.0);
+    }
+
+    #[test]
+    fn own_inertia_is_moved_to_cg() {
+        let under_test = MassProperties::from_components(&[
+            MassComponent::point("body", 2.0, Vector3::new(1.0, 0.0, 0.0))
+                .with_inertia(Matrix3::identity()),
+            MassComponent::point("ballast", 2.0, Vector3::new(-1.0, 0.0, 0.0)),
+        ])
+        .unwrap();
+        assert_eq!(under_test.inertia[(0, 0)], 1.0);
+        assert_eq!(under_test.inertia[(1, 1)], 5.0);
+        assert_eq!(under_test.inertia[(2, 2)], 5.0);
+    }
+
+    #[test]
+    fn nothing_to_weigh_is_an_error() {
+        assert_eq!(
+            MassProperties::from_components(&[]).err(),
+            Some(MassError::NoMass)
+        );
+        let weightless = [MassComponent::point("ghost", 0.0, Vector3::zeros())];
+        assert_eq!(
+            MassProperties::from_components(&weightless).err(),
+            Some(MassError::NoMass)
+        );
+        let broken = [
+            MassComponent::point("engine", 100.0, Vector3::zeros()),
+            MassComponent::point("fuel", f64::NAN, Vector3::zeros()),
+        ];
+        assert_eq!(
+            MassProperties::from_components(&broken).err(),
+            Some(MassError::BadComponent {
+                name: "fuel".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn masses_in_a_line_are_an_error() {
+        let single = [MassComponent::point("x", 100.0, Vector3::zeros())];
+        assert_eq!(
+            MassProperties::from_components(&single).err(),
+            Some(MassError::SingularInertia)
+        );
+        let in_a_line = [
+            MassComponent::point("engine", 100.0, Vector3::new(1.5, 0.0, 0.0)),
+            MassComponent::point("pilot", 80.0, Vector3::new(-0.5, 0.0, 0.0)),
+            MassComponent::point("fuel", 50.0, Vector3::new(0.0, 0.0, 0.0)),
+        ];
+        assert_eq!(
+            MassProperties::from_components(&in_a_line).err(),
+            Some(MassError::SingularInertia)
+        );
+    }
+
+    #[test]
+    fn inverse_comes_with_the_inertia() {
+        let under_test = MassProperties::from_components(&[
+            MassComponent::point("engine", 10.0, Vector3::new(1.0, 0.0, 1.0)),
+            MassComponent::point("tail", 10.0, Vector3::new(-1.0, 0.0, -1.0)),
+            body(Vector3::zeros()),
+        ])
+        .unwrap();
+        let product = under_test.inertia * under_test.inertia_inverse;
+        assert!((product - Matrix3::identity()).norm() < 1e-12);
+    }
+}
@@ -1,104 +1,533 @@
-use nalgebra::{Isometry3, Point3, UnitQuaternion, Vector, Vector3};
+use std::f64::consts::PI;
 
-use crate::{cockpit::Cockpit, engine::Engine, wing::Wing};
+use nalgebra::{Isometry3, Matrix3, Vector3};
+
+use crate::{
+    aero::{AeroComponent, FixedGear},
+    airflow::Airflow,
+    atmosphere::Atmosphere,
+    cockpit::Cockpit,
+    control::{ControlSurface, Mixing},
+    damage::{DamageMonitor, Severity, SimEvent, StrikeKind, StrikePoint},
+    downwash::Downwash,
+    earth::{EarthModel, FlatEarth, Geodetic},
+    engine::Engine,
+    fuselage::Fuselage,
+    gear::{BrakeSide, LandingGear, Tyre},
+    high_lift::{Flap, FlapKind},
+    integrator::{Integrator, RigidBodyState, SemiImplicitEuler, StateDerivative},
+    mass::{MassComponent, MassError, MassProperties},
+    planform::Planform,
+    side::Side,
+    state::{self, PlaneState, STANDARD_GRAVITY},
+    wing::{Root, Wing},
+};
 
 pub struct Plane {
-    wings: Vec<Wing>,
-    mass: f64,
+    /// wings, fuselage and anything else the air pushes on
+    components: Vec<Box<dyn AeroComponent>>,
+    masses: Vec<MassComponent>,
+    mass_properties: MassProperties,
+    /// pose of the cg, everything on the plane is positioned relative to the datum
     pub transform_in_world: Isometry3<f64>,
-    pub transform_rate_in_world: Isometry3<f64>,
+    /// velocity of the cg in world coordinates, m/s
+    pub velocity_in_world: Vector3<f64>,
+    /// body axis rates p, q, r in rad/s
+    pub angular_velocity: Vector3<f64>,
     engine: Engine,
+    landing_gear: Vec<LandingGear>,
+    strike_points: Vec<StrikePoint>,
+    damage: DamageMonitor,
+    /// main wing on to the tail
+    downwash: Downwash,
+    /// seconds of physics run so far
+    time: f64,
+    /// what the pilot was doing on the last step and how long it was, for working out accelerations afterwards
+    last_controls: Cockpit,
+    last_dt: f64,
+    integrator: Box<dyn Integrator>,
+    earth: Box<dyn EarthModel>,
 }
 
 impl Plane {
     /// coordinate system: x forwards, y right, z down
-
+    /// the world is north east down, starting at the earth model's origin
     pub fn new_solid_guess() -> Plane {
-        let wings = vec![
-            Wing::new_area_location(17.16, Vector3::new(0.25, 0.0, 0.0)),
-            Wing::new_area_location(5.0, Vector3::new(-5.0, 0.0, 0.0)),
-        ];
-        let mass = 1160.0;
-        //let position = Vector3::new(0.0, 0.0, -0.35);
-        let position = Vector3::new(-150.0, 0.0, 0.25);
+        let components = Plane::solid_guess_components();
+        let masses = Plane::solid_guess_masses();
+        // sitting on the gear
+        let position = Vector3::new(-150.0, 0.0, -0.81);
         let velocity = Vector3::new(0.0, 0.0, 0.0);
         Plane {
-            wings,
-            mass,
+            components,
+            mass_properties: MassProperties::from_components(&masses)
+                .expect("the solid guess weighs something"),
+            masses,
             transform_in_world: Isometry3::new(position, nalgebra::zero()),
-            transform_rate_in_world: Isometry3::new(velocity, nalgebra::zero()),
+            velocity_in_world: velocity,
+            angular_velocity: Vector3::zeros(),
             engine: Engine::new(),
+            landing_gear: Plane::solid_guess_gear(),
+            strike_points: Plane::solid_guess_strike_points(),
+            damage: DamageMonitor::new(),
+            downwash: Plane::solid_guess_downwash(),
+            time: 0.0,
+            last_controls: Cockpit::new(),
+            last_dt: 0.0,
+            integrator: Box::new(SemiImplicitEuler),
+            earth: Box::new(FlatEarth::new()),
         }
     }
 
     pub fn new_in_flight() -> Plane {
-        let wings = vec![
-            Wing::new_area_location(17.16, Vector3::new(0.25, 0.0, 0.0)),
-            Wing::new_area_location(5.0, Vector3::new(-5.0, 0.0, 0.0)),
-        ];
-        let mass = 1160.0;
+        let components = Plane::solid_guess_components();
+        let masses = Plane::solid_guess_masses();
         //let position = Vector3::new(0.0, 0.0, -0.35);
         let position = Vector3::new(-150.0, 0.0, -50.25);
         let velocity = Vector3::new(40.0, 0.0, 0.0);
-        let angular_velocity = UnitQuaternion::from_euler_angles(0.0, 0.0, 0.0);
-        let pointing = UnitQuaternion::from_euler_angles(0.0, 0.0, 0.0);
         Plane {
-            wings,
-            mass,
+            components,
+            mass_properties: MassProperties::from_components(&masses)
+                .expect("the solid guess weighs something"),
+            masses,
             transform_in_world: Isometry3::new(position, nalgebra::zero()),
-            transform_rate_in_world: Isometry3::new(velocity, nalgebra::zero()),
+            velocity_in_world: velocity,
+            angular_velocity: Vector3::zeros(),
             engine: Engine::new(),
+            landing_gear: Plane::solid_guess_gear(),
+            strike_points: Plane::solid_guess_strike_points(),
+            damage: DamageMonitor::new(),
+            downwash: Plane::solid_guess_downwash(),
+            time: 0.0,
+            last_controls: Cockpit::new(),
+            last_dt: 0.0,
+            integrator: Box::new(SemiImplicitEuler),
+            earth: Box::new(FlatEarth::new()),
         }
     }
 
-    pub fn run_physics(&mut self, dt: f64, controls: &Cockpit) {
-        let aero_forces: Vector3<f64> = self
-            .wings
+    /// wings and tail, the fuselage and the fixed gear hanging underneath
+    fn solid_guess_components() -> Vec<Box<dyn AeroComponent>> {
+        let mut components: Vec<Box<dyn AeroComponent>> = Plane::solid_guess_wings()
+            .into_iter()
+            .map(|wing| Box::new(wing) as Box<dyn AeroComponent>)
+            .collect();
+        components.push(Box::new(Fuselage::new(
+            Vector3::new(-1.3, 0.0, 0.1),
+            7.6,
+            1.1,
+        )));
+        for gear in Plane::solid_guess_gear() {
+            let axle = gear.wheel_bottom() - Vector3::new(0.0, 0.0, gear.tyre.radius);
+            components.push(Box::new(
+                FixedGear::wheel(axle, 2.0 * gear.tyre.radius, 0.15).with_fairing(),
+            ));
+        }
+        components
+    }
+
+    /// one half of the main wing, it still carries the cooling, strut and interference drag
+    /// both roots on the centreline, 3 degrees of washout so the tips stall last
+    fn solid_guess_main_wing_half(side: Side) -> Wing {
+        let root = match side {
+            Side::Left => Root::RightEnd,
+            Side::Right => Root::LeftEnd,
+        };
+        let planform = Planform::new(root, 5.48, 1.842, 1.289)
+            .with_washout(3.0_f64.to_radians())
+            .with_dihedral(1.7_f64.to_radians());
+        Wing::from_planform(planform, Vector3::new(0.25, 0.0, 0.0), 6)
+            .with_drag(0.03, 0.7)
+            .with_pitching_moment(-0.05)
+            .with_control_surface(ControlSurface::new(
+                0.2,
+                15.0_f64.to_radians(),
+                20.0_f64.to_radians(),
+                Mixing::aileron(side),
+            ))
+            .with_flap(Flap::new(
+                FlapKind::Fowler,
+                0.3,
+                0.55,
+                [0.0_f64, 10.0, 20.0, 30.0]
+                    .iter()
+                    .map(|stage| stage.to_radians())
+                    .collect(),
+                8.0,
+            ))
+    }
+
+    fn solid_guess_wings() -> Vec<Wing> {
+        vec![
+            Plane::solid_guess_main_wing_half(Side::Left),
+            Plane::solid_guess_main_wing_half(Side::Right),
+            Wing::new_area_location(5.0, Vector3::new(-5.0, 0.0, 0.0))
+                .in_downwash()
+                .with_control_surface(ControlSurface::new(
+                    0.4,
+                    15.0_f64.to_radians(),
+                    25.0_f64.to_radians(),
+                    Mixing::elevator(),
+                )),
+            Plane::fin(),
+        ]
+    }
+
+    /// epsilon = 2 CL / (pi AR) for the aspect ratio 7 main wing
+    /// the washout takes some lift off at zero alpha, so that's measured off the wing itself
+    fn solid_guess_downwash() -> Downwash {
+        let halves = [Side::Left, Side::Right].map(Plane::solid_guess_main_wing_half);
+        let airflow = Airflow::uniform(Vector3::new(50.0, 0.0, 0.0));
+        let lift: f64 = halves
             .iter()
-            .map(|wing| wing.calculate_forces(&self.plane_velocity()))
+            .map(|half| -half.calculate_forces(&airflow).z)
             .sum();
+        let area: f64 = halves.iter().map(|half| half.area()).sum();
+        let cl0 = lift / (0.5 * airflow.atmosphere.density * 50.0_f64.powi(2) * area);
+        let lift_slope = 2.0 * PI / (1.0 + 2.0 / 7.0);
+        Downwash::new(2.0 * cl0 / (PI * 7.0), 2.0 * lift_slope / (PI * 7.0), 5.25)
     }
 
-    fn calculate_all_forces(&mut self) -> Vector3<f64> {
-        return nalgebra::zero();
+    /// the fin is a wing on its side, sticking up above the tail
+    fn fin() -> Wing {
+        let rudder = ControlSurface::new(
+            0.4,
+            16.0_f64.to_radians(),
+            16.0_f64.to_radians(),
+            Mixing::rudder(),
+        );
+        Wing::new_fin(1.6, Vector3::new(-5.2, 0.0, -0.7))
+            .with_aspect_ratio(1.6)
+            .with_control_surface(rudder)
     }
 
-    pub fn plane_velocity(&self) -> Vector3<f64> {
-        return (self.transform_in_world.rotation.inverse()
-            * self.transform_rate_in_world.translation)
-            .translation
-            .vector;
+    /// roughly a loaded 172, adds up to 1160 kg
+    fn solid_guess_masses() -> Vec<MassComponent> {
+        vec![
+            MassComponent::point("engine", 180.0, Vector3::new(1.5, 0.0, 0.1)),
+            MassComponent::point("airframe", 530.0, Vector3::new(-0.4, 0.0, 0.2))
+                .with_inertia(Matrix3::from_diagonal(&Vector3::new(60.0, 700.0, 700.0))),
+            MassComponent::point("left wing", 70.0, Vector3::new(0.3, -2.5, 0.0)),
+            MassComponent::point("right wing", 70.0, Vector3::new(0.3, 2.5, 0.0)),
+            MassComponent::point("tail", 30.0, Vector3::new(-5.0, 0.0, 0.0)),
+            MassComponent::point("left fuel", 60.0, Vector3::new(0.3, -1.5, 0.0)),
+            MassComponent::point("right fuel", 60.0, Vector3::new(0.3, 1.5, 0.0)),
+            MassComponent::point("pilot", 80.0, Vector3::new(0.2, -0.3, 0.3)),
+            MassComponent::point("payload", 80.0, Vector3::new(-0.7, 0.0, 0.3)),
+        ]
     }
 
-    fn ground_moments(&self) -> Vector3<f64> {
-        if self.transform_in_world.translation.z < -1.0 {
-            return Vector3::zeros();
+    /// tricycle gear, mains just behind the cg so it sits nose wheel down
+    fn solid_guess_gear() -> Vec<LandingGear> {
+        let main_tyre = || Tyre::new(0.25, 200000.0, 500.0);
+        vec![
+            LandingGear::new(
+                "nose",
+                Vector3::new(1.3, 0.0, 0.7),
+                0.15,
+                50000.0,
+                2500.0,
+                Tyre::new(0.2, 150000.0, 300.0),
+            )
+            .with_steering(0.3),
+            LandingGear::new(
+                "left main",
+                Vector3::new(-0.5, -1.2, 0.6),
+                0.2,
+                60000.0,
+                5600.0,
+                main_tyre(),
+            )
+            .with_brake(BrakeSide::Left),
+            LandingGear::new(
+                "right main",
+                Vector3::new(-0.5, 1.2, 0.6),
+                0.2,
+                60000.0,
+                5600.0,
+                main_tyre(),
+            )
+            .with_brake(BrakeSide::Right),
+        ]
+    }
+
+    /// wingtips and tail a bit outside the gear, the prop hangs below the nose
+    fn solid_guess_strike_points() -> Vec<StrikePoint> {
+        vec![
+            StrikePoint::new(
+                StrikeKind::Wingtip(Side::Left),
+                Vector3::new(0.3, -5.45, 0.0),
+            ),
+            StrikePoint::new(
+                StrikeKind::Wingtip(Side::Right),
+                Vector3::new(0.3, 5.45, 0.0),
+            ),
+            StrikePoint::new(StrikeKind::Tail, Vector3::new(-4.5, 0.0, 0.0)),
+            StrikePoint::new(StrikeKind::Propeller, Vector3::new(2.1, 0.0, 0.75)),
+            StrikePoint::new(StrikeKind::Belly, Vector3::new(0.5, 0.0, 0.55)),
+            StrikePoint::new(StrikeKind::Belly, Vector3::new(-2.0, 0.0, 0.45)),
+        ]
+    }
+
+    pub fn landing_gear(&self) -> &[LandingGear] {
+        &self.landing_gear
+    }
+
+    /// just the wings out of the aero components
+    pub fn wings(&self) -> Vec<&Wing> {
+        self.components
+            .iter()
+            .filter_map(|component| component.as_wing())
+            .collect()
+    }
+
+    pub fn mass_properties(&self) -> &MassProperties {
+        &self.mass_properties
+    }
+
+    /// swap out what the plane is carrying, the cg and inertia are worked out again
+    /// the plane keeps what it had if the new masses don't make sense
+    pub fn set_masses(&mut self, masses: Vec<MassComponent>) -> Result<(), MassError> {
+        self.mass_properties = MassProperties::from_components(&masses)?;
+        self.masses = masses;
+        Ok(())
+    }
+
+    pub fn masses(&self) -> &[MassComponent] {
+        &self.masses
+    }
+
+    /// advance the aircraft by dt seconds with whichever integrator is set
+    pub fn run_physics(&mut self, dt: f64, controls: &Cockpit) {
+        for component in &mut self.components {
+            component.update_controls(controls);
+            component.move_actuators(dt);
+        }
+        let state = self.rigid_body_state();
+        let next = self
+            .integrator
+            .step(&state, dt, &|state| self.derivative(state, controls, dt));
+        self.transform_in_world = next.transform;
+        self.velocity_in_world = next.velocity;
+        self.angular_velocity = next.angular_velocity;
+        self.time += dt;
+        self.downwash
+            .record(self.time, state::alpha(&Plane::velocity_body(&next)));
+        self.last_controls = controls.clone();
+        self.last_dt = dt;
+        let cg = self.mass_properties.cg;
+        for gear in &mut self.landing_gear {
+            gear.update_hold(&next, &cg, self.earth.as_ref(), controls);
+        }
+        self.check_for_damage(&next, controls, dt);
+    }
+
+    /// looks for anything that has just touched the ground
+    fn check_for_damage(&mut self, state: &RigidBodyState, controls: &Cockpit, dt: f64) {
+        let cg = self.mass_properties.cg;
+        let gear_contacts: Vec<_> = self
+            .landing_gear
+            .iter()
+            .map(|gear| {
+                gear.contact(state, &cg, self.earth.as_ref(), controls, dt)
+                    .map(|contact| {
+                        let position = state.transform.translation.vector
+                            + state.transform.rotation * contact.point;
+                        (contact.velocity, position)
+                    })
+            })
+            .collect();
+        let strike_contacts: Vec<_> = self
+            .strike_points
+            .iter()
+            .map(|point| (point.kind, point.contact(state, &cg, self.earth.as_ref())))
+            .collect();
+        let gear_down = self
+            .landing_gear
+            .iter()
+            .all(|gear| gear.is_extended(controls));
+        self.damage
+            .check(self.time, &gear_contacts, &strike_contacts, gear_down);
+    }
+
+    /// everything that has happened since the last call, oldest first
+    pub fn drain_events(&mut self) -> Vec<SimEvent> {
+        self.damage.drain_events()
+    }
+
+    /// the worst damage so far, none if the plane is still in one piece
+    pub fn damage(&self) -> Option<Severity> {
+        self.damage.worst_severity()
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.damage() == Some(Severity::Destroyed)
+    }
+
+    pub fn strike_points(&self) -> &[StrikePoint] {
+        &self.strike_points
+    }
+
+    /// defaults to semi implicit euler
+    pub fn set_integrator(&mut self, integrator: Box<dyn Integrator>) {
+        self.integrator = integrator;
+    }
+
+    /// defaults to a flat earth with constant g
+    pub fn set_earth_model(&mut self, earth: Box<dyn EarthModel>) {
+        self.earth = earth;
+    }
+
+    /// where the plane is on the real earth
+    pub fn geodetic(&self) -> Geodetic {
+        self.earth
+            .geodetic(&self.transform_in_world.translation.vector)
+    }
+
+    /// snapshot for anything outside the physics
+    /// accelerations are worked out here rather than every step, with the last step's controls
+    pub fn state(&self) -> PlaneState {
+        let rigid_body = self.rigid_body_state();
+        let position = rigid_body.transform.translation.vector;
+        let rotation = rigid_body.transform.rotation;
+        let velocity_body = Plane::velocity_body(&rigid_body);
+        let derivative = self.derivative(&rigid_body, &self.last_controls, self.last_dt);
+        let acceleration = derivative.acceleration;
+        let specific_force = acceleration
+            - self.earth.gravity(&position)
+            - self
+                .earth
+                .frame_acceleration(&position, &rigid_body.velocity);
+        let specific_force_body = rotation.inverse() * specific_force;
+        let (roll, pitch, heading) = rotation.euler_angles();
+        let airspeed = velocity_body.norm();
+        PlaneState {
+            time: self.time,
+            position,
+            geodetic: self.geodetic(),
+            height_above_ground: self.earth.height_above_ground(&position),
+            velocity_ned: rigid_body.velocity,
+            velocity_body,
+            attitude: rotation,
+            roll,
+            pitch,
+            heading,
+            body_rates: rigid_body.angular_velocity,
+            acceleration_ned: acceleration,
+            angular_acceleration: derivative.angular_acceleration,
+            specific_force_body,
+            load_factor: -specific_force_body.z / STANDARD_GRAVITY,
+            alpha: state::alpha(&velocity_body),
+            beta: state::beta(&velocity_body),
+            airspeed,
+            mach: airspeed / self.atmosphere_at(&position).speed_of_sound,
         }
-        let pointing_contrib = Vector3::new(
-            (&self.transform_in_world.rotation.euler_angles().1 + 0.1) * -100000.0,
-            0.0,
-            0.0,
+    }
+
+    pub fn rigid_body_state(&self) -> RigidBodyState {
+        RigidBodyState::new(
+            self.transform_in_world,
+            self.velocity_in_world,
+            self.angular_velocity,
+        )
+    }
+
+    /// turn the forces and moments at a state into accelerations
+    fn derivative(&self, state: &RigidBodyState, controls: &Cockpit, dt: f64) -> StateDerivative {
+        let (forces_world, moments_body) = self.calculate_all_forces(state, controls, dt);
+
+        let position = state.transform.translation.vector;
+        let acceleration = forces_world / self.mass_properties.mass
+            + self.earth.gravity(&position)
+            + self.earth.frame_acceleration(&position, &state.velocity);
+
+        let inertia = self.mass_properties.inertia;
+        let angular_velocity = state.angular_velocity;
+        let gyroscopic = angular_velocity.cross(&(inertia * angular_velocity));
+        let angular_acceleration =
+            self.mass_properties.inertia_inverse * (moments_body - gyroscopic);
+
+        StateDerivative {
+            velocity: state.velocity,
+            angular_velocity: state.angular_velocity,
+            acceleration,
+            angular_acceleration,
+        }
+    }
+
+    /// forces are returned in world coordinates, moments in body coordinates about the cg
+    /// gravity isn't included, the earth model adds it as an acceleration
+    /// dt is the step being taken, the tyres need it to know how hard to grip
+    fn calculate_all_forces(
+        &self,
+        state: &RigidBodyState,
+        controls: &Cockpit,
+        dt: f64,
+    ) -> (Vector3<f64>, Vector3<f64>) {
+        let cg = self.mass_properties.cg;
+        let velocity_body = Plane::velocity_body(state);
+        let downwash = self.downwash.angle(
+            self.time,
+            state::alpha(&velocity_body),
+            velocity_body.norm(),
         );
+        let height = self
+            .earth
+            .height_above_ground(&state.transform.translation.vector);
+        let airflow = Airflow::new(velocity_body, state.angular_velocity, cg)
+            .with_downwash(downwash)
+            .with_ground(height, state.transform.rotation)
+            .with_atmosphere(self.atmosphere_at(&state.transform.translation.vector));
 
-        let rotating_contrib = Vector3::new(
-            &self.transform_rate_in_world.rotation.euler_angles().1 * -10.0,
-            0.0,
-            0.0,
+        let (aero_forces, aero_moments) = self.components.iter().fold(
+            (Vector3::zeros(), Vector3::zeros()),
+            |(forces, moments), component| {
+                let (component_forces, component_moments) = component.loads(&airflow);
+                (forces + component_forces, moments + component_moments)
+            },
         );
 
-        pointing_contrib + rotating_contrib
+        let engine_force = self.engine_force(controls);
+        let forces_body = aero_forces + engine_force;
+
+        // aero moments are already about the cg, the thrust line goes through the datum
+        let moments_about_cg = aero_moments - cg.cross(&engine_force);
+
+        let (gear_forces, gear_moments) = self
+            .landing_gear
+            .iter()
+            .filter_map(|gear| gear.contact(state, &cg, self.earth.as_ref(), controls, dt))
+            .fold(
+                (Vector3::zeros(), Vector3::zeros()),
+                |(force, moment), contact| (force + contact.force, moment + contact.moment),
+            );
+
+        // bits of airframe scraping along the ground
+        let (scrape_forces, scrape_moments) = self
+            .strike_points
+            .iter()
+            .filter_map(|point| point.contact(state, &cg, self.earth.as_ref()))
+            .fold(
+                (Vector3::zeros(), Vector3::zeros()),
+                |(force, moment), contact| (force + contact.force, moment + contact.moment),
+            );
+
+        let forces_world = state.transform.rotation * (forces_body + gear_forces + scrape_forces);
+        let moments_body = moments_about_cg + gear_moments + scrape_moments;
+        (forces_world, moments_body)
     }
 
-    fn ground_force(&self) -> f64 {
-        let k = 15000.0;
-        let k_d = 1500.0;
-        if self.transform_in_world.translation.z < -1.0 {
-            return 0.0;
-        } else {
-            let spring = k * (self.transform_in_world.translation.z + 1.0);
-            let damper = k_d * self.transform_rate_in_world.translation.z;
-            return spring + damper;
-        }
+    /// standard day at the plane's altitude
+    fn atmosphere_at(&self, position: &Vector3<f64>) -> Atmosphere {
+        Atmosphere::standard(self.earth.geodetic(position).altitude)
+    }
+
+    pub fn plane_velocity(&self) -> Vector3<f64> {
+        Plane::velocity_body(&self.rigid_body_state())
+    }
+
+    fn velocity_body(state: &RigidBodyState) -> Vector3<f64> {
+        state.transform.rotation.inverse() * state.velocity
     }
 
     fn engine_force(&self, controls: &Cockpit) -> Vector3<f64> {
@@ -110,17 +539,47 @@ impl Plane {
 mod test {
     use std::f64::consts::PI;
 
-    use nalgebra::{Isometry3, UnitQuaternion, Vector3};
+    use nalgebra::{Isometry3, Matrix3, UnitQuaternion, Vector3};
 
-    use crate::{engine::Engine, plane::Plane, wing::Wing};
+    use crate::{
+        airflow::Airflow,
+        atmosphere::Atmosphere,
+        cockpit::Cockpit,
+        damage::{DamageMonitor, Severity, SimEventKind},
+        downwash::Downwash,
+        earth::{FlatEarth, Geodetic, RotatingEarth},
+        engine::Engine,
+        integrator::{RungeKutta4, SemiImplicitEuler},
+        mass::{MassComponent, MassProperties},
+        plane::Plane,
+        side::Side,
+        wing::Wing,
+    };
 
     fn make_default_aircraft() -> Plane {
+        let masses = vec![
+            MassComponent::point("test", 10.0, Vector3::zeros()).with_inertia(Matrix3::identity()),
+        ];
         Plane {
-            wings: vec![Wing::new_area_location(5.0, Vector3::new(-1.0, 0.0, 0.0))],
-            mass: 10.0,
+            components: vec![Box::new(Wing::new_area_location(
+                5.0,
+                Vector3::new(-1.0, 0.0, 0.0),
+            ))],
+            mass_properties: MassProperties::from_components(&masses).unwrap(),
+            masses,
             engine: Engine::new(),
+            landing_gear: Vec::new(),
+            strike_points: Vec::new(),
+            damage: DamageMonitor::new(),
+            downwash: Downwash::none(),
+            time: 0.0,
+            last_controls: Cockpit::new(),
+            last_dt: 0.0,
+            integrator: Box::new(SemiImplicitEuler),
+            earth: Box::new(FlatEarth::new()),
             transform_in_world: Isometry3::new(nalgebra::zero(), nalgebra::zero()),
-            transform_rate_in_world: Isometry3::new(Vector3::new(10.0, 0.0, 0.0), nalgebra::zero()),
+            velocity_in_world: Vector3::new(10.0, 0.0, 0.0),
+            angular_velocity: Vector3::zeros(),
         }
     }
 
@@ -147,4 +606,559 @@ mod test {
         let velocity = under_test.plane_velocity();
         assert!((velocity.z - velocity.x).abs() < 1e-6);
     }
+
+    #[test]
+    fn plane_velocity_while_yawing() {
+        let mut under_test = make_default_aircraft();
+        under_test.components.clear();
+        under_test.transform_in_world.translation.vector = Vector3::new(0.0, 0.0, -1000.0);
+        under_test.angular_velocity = Vector3::new(0.0, 0.0, PI / 2.0);
+        let controls = Cockpit::new();
+        for _ in 0..100 {
+            under_test.run_physics(0.01, &controls);
+        }
+        // now pointing east but still moving north, so the air comes from the left
+        let velocity = under_test.plane_velocity();
+        assert!(velocity.x.abs() < 1e-6);
+        assert!((velocity.y - -10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn body_rates_follow_the_body() {
+        let mut under_test = make_default_aircraft();
+        under_test.components.clear();
+        under_test.transform_in_world.translation.vector = Vector3::new(0.0, 0.0, -1000.0);
+        under_test.transform_in_world.rotation =
+            UnitQuaternion::from_euler_angles(PI / 2.0, 0.0, 0.0);
+        // pitching up while on a knife edge turns the nose to the east
+        under_test.angular_velocity = Vector3::new(0.0, PI / 2.0, 0.0);
+        let controls = Cockpit::new();
+        for _ in 0..100 {
+            under_test.run_physics(0.01, &controls);
+        }
+        let nose = under_test.transform_in_world.rotation * Vector3::x();
+        assert!((nose - Vector3::new(0.0, 1.0, 0.0)).norm() < 1e-6);
+        // the body rate isn't changed by the rotation
+        assert!((under_test.angular_velocity.y - PI / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn attitude_stays_normalised() {
+        let mut under_test = make_default_aircraft();
+        under_test.components.clear();
+        under_test.transform_in_world.translation.vector = Vector3::new(0.0, 0.0, -1e6);
+        under_test.angular_velocity = Vector3::new(1.3, -0.7, 2.1);
+        let controls = Cockpit::new();
+        for _ in 0..10000 {
+            under_test.run_physics(0.01, &controls);
+        }
+        let norm = under_test.transform_in_world.rotation.quaternion().norm();
+        assert!((norm - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn falls_under_gravity_without_wings() {
+        let mut under_test = make_default_aircraft();
+        under_test.components.clear();
+        under_test.transform_in_world.translation.vector = Vector3::new(0.0, 0.0, -1000.0);
+        let controls = Cockpit::new();
+        for _ in 0..100 {
+            under_test.run_physics(0.01, &controls);
+        }
+        let velocity = under_test.velocity_in_world;
+        assert!((velocity.z - 9.81).abs() < 1e-6);
+        assert!((velocity.x - 10.0).abs() < 1e-6);
+        assert!((under_test.transform_in_world.translation.x - 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn falls_with_runge_kutta() {
+        let mut under_test = make_default_aircraft();
+        under_test.components.clear();
+        under_test.set_integrator(Box::new(RungeKutta4));
+        under_test.transform_in_world.translation.vector = Vector3::new(0.0, 0.0, -1000.0);
+        let controls = Cockpit::new();
+        for _ in 0..100 {
+            under_test.run_physics(0.01, &controls);
+        }
+        // exact for constant acceleration
+        let drop = under_test.transform_in_world.translation.z + 1000.0;
+        assert!((drop - 0.5 * 9.81).abs() < 1e-9);
+    }
+
+    #[test]
+    fn constant_angular_velocity_keeps_rolling() {
+        let mut under_test = make_default_aircraft();
+        under_test.components.clear();
+        under_test.transform_in_world.translation.vector = Vector3::new(0.0, 0.0, -1000.0);
+        under_test.angular_velocity = Vector3::new(PI / 2.0, 0.0, 0.0);
+        let controls = Cockpit::new();
+        for _ in 0..100 {
+            under_test.run_physics(0.01, &controls);
+        }
+        let (roll, pitch, yaw) = under_test.transform_in_world.rotation.euler_angles();
+        assert!((roll - PI / 2.0).abs() < 1e-6);
+        assert!(pitch.abs() < 1e-6);
+        assert!(yaw.abs() < 1e-6);
+    }
+
+    #[test]
+    fn rotating_earth_moves_latitude() {
+        let mut under_test = make_default_aircraft();
+        under_test.components.clear();
+        under_test.set_earth_model(Box::new(RotatingEarth::wgs84(Geodetic::from_degrees(
+            45.0, 7.0, 0.0,
+        ))));
+        under_test.transform_in_world.translation.vector = Vector3::new(0.0, 0.0, -3000.0);
+        under_test.velocity_in_world = Vector3::new(100.0, 0.0, 0.0);
+        let controls = Cockpit::new();
+        for _ in 0..1000 {
+            under_test.run_physics(0.01, &controls);
+        }
+        let place = under_test.geodetic();
+        assert!((place.latitude.to_degrees() - 45.009).abs() < 1e-3);
+        // coriolis pushes a northbound plane to the east in the northern hemisphere
+        assert!(under_test.transform_in_world.translation.y > 0.0);
+        // ten seconds of falling, gravity is a bit under 9.81 this high up
+        assert!(under_test.velocity_in_world.z > 97.0 && under_test.velocity_in_world.z < 98.1);
+    }
+
+    #[test]
+    fn lands_on_the_curved_ground_far_from_the_origin() {
+        let mut under_test = Plane::new_solid_guess();
+        under_test.components.clear();
+        let origin = Geodetic::from_degrees(45.0, 7.0, 200.0);
+        under_test.set_earth_model(Box::new(RotatingEarth::wgs84(origin)));
+        // 100 km north the ground has dropped hundreds of metres below the tangent plane
+        let mut position = Vector3::new(100000.0, 0.0, 0.0);
+        for _ in 0..3 {
+            under_test.transform_in_world.translation.vector = position;
+            position.z += under_test.state().height_above_ground - 0.9;
+        }
+        assert!(position.z > 700.0);
+        under_test.transform_in_world.translation.vector = position;
+        let controls = Cockpit::new();
+        for _ in 0..2000 {
+            under_test.run_physics(0.0025, &controls);
+        }
+        let state = under_test.state();
+        assert!((state.height_above_ground - 0.81).abs() < 0.05);
+        assert!((state.height_above_ground - (state.geodetic.altitude - 200.0)).abs() < 1e-6);
+        assert_eq!(under_test.damage(), None);
+    }
+
+    #[test]
+    fn sits_on_its_gear() {
+        let mut under_test = Plane::new_solid_guess();
+        under_test.components.clear();
+        let start = under_test.transform_in_world.translation.z;
+        let controls = Cockpit::new();
+        for _ in 0..2000 {
+            under_test.run_physics(0.0025, &controls);
+        }
+        let (roll, pitch, _) = under_test.transform_in_world.rotation.euler_angles();
+        assert!((under_test.transform_in_world.translation.z - start).abs() < 0.05);
+        assert!(under_test.velocity_in_world.norm() < 1e-2);
+        assert!(pitch.abs() < 0.05);
+        // the pilot sits on the left
+        assert!(roll < 0.0 && roll > -0.01);
+    }
+
+    #[test]
+    fn bounces_and_settles_after_a_drop() {
+        let mut under_test = Plane::new_solid_guess();
+        under_test.components.clear();
+        under_test.transform_in_world.translation.z -= 1.0;
+        let controls = Cockpit::new();
+        let mut left_ground = false;
+        for step in 0..4000 {
+            under_test.run_physics(0.0025, &controls);
+            if step > 200 && under_test.velocity_in_world.z < -0.1 {
+                left_ground = true;
+            }
+        }
+        assert!(left_ground);
+        assert!(under_test.velocity_in_world.norm() < 1e-2);
+    }
+
+    fn drop_rolled(mut under_test: Plane, roll: f64, seconds: f64) -> f64 {
+        under_test.components.clear();
+        under_test.transform_in_world.translation.z = -2.0;
+        under_test.transform_in_world.rotation = UnitQuaternion::from_euler_angles(roll, 0.0, 0.0);
+        let controls = Cockpit::new();
+        for _ in 0..(seconds / 0.0025) as usize {
+            under_test.run_physics(0.0025, &controls);
+        }
+        under_test.transform_in_world.rotation.euler_angles().0
+    }
+
+    #[test]
+    fn rocks_back_onto_both_mains() {
+        assert!(drop_rolled(Plane::new_solid_guess(), 0.3, 3.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn tips_over_past_the_gear_track() {
+        // mains are 1.2 out and about 0.9 below the cg, with no wingtip to catch it
+        let mut under_test = Plane::new_solid_guess();
+        under_test.strike_points.clear();
+        assert!(drop_rolled(under_test, 1.0, 1.5) > 1.5);
+    }
+
+    fn run_for(under_test: &mut Plane, seconds: f64, controls: &Cockpit) -> Vec<SimEventKind> {
+        for _ in 0..(seconds / 0.0025) as usize {
+            under_test.run_physics(0.0025, controls);
+        }
+        under_test
+            .drain_events()
+            .iter()
+            .map(|event| event.kind)
+            .collect()
+    }
+
+    #[test]
+    fn tipping_over_lands_on_a_wingtip() {
+        let mut under_test = Plane::new_solid_guess();
+        under_test.components.clear();
+        under_test.angular_velocity = Vector3::new(3.0, 0.0, 0.0);
+        let events = run_for(&mut under_test, 2.0, &Cockpit::new());
+        assert!(events.contains(&SimEventKind::WingtipStrike { side: Side::Right }));
+        // the wing stops it going right over and it drops back onto its wheels
+        let (roll, _, _) = under_test.transform_in_world.rotation.euler_angles();
+        assert!(roll.abs() < 0.05);
+    }
+
+    #[test]
+    fn gentle_drop_is_not_damage() {
+        let mut under_test = Plane::new_solid_guess();
+        under_test.components.clear();
+        under_test.transform_in_world.translation.z -= 0.1;
+        assert!(run_for(&mut under_test, 2.0, &Cockpit::new()).is_empty());
+        assert_eq!(under_test.damage(), None);
+    }
+
+    #[test]
+    fn drop_from_a_metre_is_a_hard_landing() {
+        let mut under_test = Plane::new_solid_guess();
+        under_test.components.clear();
+        under_test.transform_in_world.translation.z -= 1.0;
+        let events = run_for(&mut under_test, 2.0, &Cockpit::new());
+        assert!(matches!(events[0], SimEventKind::HardLanding { sink_rate } if sink_rate > 4.0));
+        assert_eq!(under_test.damage(), Some(Severity::Substantial));
+    }
+
+    #[test]
+    fn diving_into_the_ground_destroys_it() {
+        let mut under_test = Plane::new_in_flight();
+        under_test.components.clear();
+        under_test.transform_in_world.translation.z = -10.0;
+        under_test.transform_in_world.rotation = UnitQuaternion::from_euler_angles(0.0, -0.6, 0.0);
+        under_test.velocity_in_world = Vector3::new(40.0, 0.0, 25.0);
+        let events = run_for(&mut under_test, 1.0, &Cockpit::new());
+        assert!(matches!(events[0], SimEventKind::TerrainImpact { .. }));
+        assert!(under_test.is_destroyed());
+    }
+
+    #[test]
+    fn belly_landing_with_the_gear_up() {
+        let mut under_test = Plane::new_solid_guess();
+        under_test.components.clear();
+        under_test.landing_gear = under_test
+            .landing_gear
+            .into_iter()
+            .map(|gear| gear.retractable())
+            .collect();
+        under_test.transform_in_world.translation.z = -1.0;
+        under_test.velocity_in_world = Vector3::new(25.0, 0.0, 1.0);
+        let mut controls = Cockpit::new();
+        controls.gear_down = false;
+        let events = run_for(&mut under_test, 10.0, &controls);
+        assert!(events.contains(&SimEventKind::GearUpLanding));
+        assert!(events.contains(&SimEventKind::PropStrike));
+        // slides to a stop on its belly rather than sinking into the ground
+        assert!(under_test.velocity_in_world.norm() < 0.1);
+        assert!(under_test.transform_in_world.translation.z < 0.0);
+    }
+
+    fn rolling_on_the_runway(speed: f64) -> Plane {
+        let mut under_test = Plane::new_solid_guess();
+        under_test.components.clear();
+        for _ in 0..400 {
+            under_test.run_physics(0.0025, &Cockpit::new());
+        }
+        under_test.velocity_in_world = Vector3::new(speed, 0.0, 0.0);
+        under_test
+    }
+
+    #[test]
+    fn coasts_to_a_stop_slowly_and_brakes_quickly() {
+        let mut coasting = rolling_on_the_runway(20.0);
+        let mut braking = rolling_on_the_runway(20.0);
+        let mut controls = Cockpit::new();
+        for _ in 0..2000 {
+            coasting.run_physics(0.0025, &controls);
+        }
+        controls.brake_left = 1.0;
+        controls.brake_right = 1.0;
+        for _ in 0..3200 {
+            braking.run_physics(0.0025, &controls);
+        }
+        assert!(coasting.velocity_in_world.x > 18.0);
+        assert!(braking.velocity_in_world.x < 0.5);
+        // 20 m/s stopping at about 0.3g, the nose wheel takes some of the load off the mains
+        assert!(braking.transform_in_world.translation.x < -150.0 + 75.0);
+    }
+
+    #[test]
+    fn brakes_hold_it_still_at_idle() {
+        let mut under_test = rolling_on_the_runway(0.0);
+        let start = under_test.transform_in_world.translation.x;
+        let mut controls = Cockpit::new();
+        // more than the rolling resistance, it would creep away with the brakes off
+        controls.throttle = 0.2;
+        controls.brake_left = 1.0;
+        controls.brake_right = 1.0;
+        for _ in 0..4000 {
+            under_test.run_physics(0.0025, &controls);
+        }
+        assert!((under_test.transform_in_world.translation.x - start).abs() < 0.05);
+        assert!(under_test.velocity_in_world.norm() < 0.01);
+    }
+
+    #[test]
+    fn brakes_hold_it_still_at_a_coarse_step() {
+        let mut under_test = rolling_on_the_runway(0.0);
+        let start = under_test.transform_in_world.translation.vector;
+        let mut controls = Cockpit::new();
+        controls.throttle = 0.2;
+        controls.brake_left = 1.0;
+        controls.brake_right = 1.0;
+        for i in 0..600 {
+            under_test.run_physics(1.0 / 60.0, &controls);
+            if i % 10 == 0 || i < 12 {
+                println!(
+                    "{i} v {:?} w {:?} z {}",
+                    under_test.velocity_in_world.as_slice(),
+                    under_test.angular_velocity.as_slice(),
+                    under_test.transform_in_world.translation.z
+                );
+            }
+        }
+        let moved = under_test.transform_in_world.translation.vector - start;
+        // settles onto the tyres and then stays put
+        assert!(moved.xy().norm() < 0.01, "{moved}");
+        assert!(under_test.velocity_in_world.norm() < 1e-3);
+    }
+
+    #[test]
+    fn full_throttle_starts_a_takeoff_roll() {
+        let mut under_test = rolling_on_the_runway(0.0);
+        let mut controls = Cockpit::new();
+        controls.throttle = 1.0;
+        for _ in 0..4000 {
+            under_test.run_physics(0.0025, &controls);
+        }
+        // 2000 N on 1160 kg less rolling resistance, for ten seconds
+        assert!(under_test.velocity_in_world.x > 15.0 && under_test.velocity_in_world.x < 17.3);
+        // the pilot's weight drags the left wheel a bit harder, so it wanders without rudder
+        assert!(under_test.velocity_in_world.y.abs() < 1.0);
+    }
+
+    #[test]
+    fn steering_and_differential_braking_turn_right() {
+        for (yaw, brake_right) in [(-1.0, 0.0), (0.0, 1.0)] {
+            let mut under_test = rolling_on_the_runway(10.0);
+            let mut controls = Cockpit::new();
+            controls.yaw = yaw;
+            controls.brake_right = brake_right;
+            for _ in 0..800 {
+                under_test.run_physics(0.0025, &controls);
+            }
+            let (_, _, heading) = under_test.transform_in_world.rotation.euler_angles();
+            assert!(heading > 0.1);
+        }
+    }
+
+    #[test]
+    fn parked_state_pulls_one_g() {
+        let mut under_test = Plane::new_solid_guess();
+        under_test.components.clear();
+        run_for(&mut under_test, 1.0, &Cockpit::new());
+        let state = under_test.state();
+        assert!((state.load_factor - 1.0).abs() < 1e-2);
+        assert!(state.acceleration_ned.norm() < 1e-2);
+        assert!((state.height_above_ground - 0.81).abs() < 0.05);
+        assert!((state.time - 1.0).abs() < 1e-9);
+        assert!(state.airspeed < 1e-2);
+    }
+
+    #[test]
+    fn state_in_a_sideways_skid() {
+        let mut under_test = make_default_aircraft();
+        under_test.transform_in_world.rotation = UnitQuaternion::from_euler_angles(0.0, 0.1, 0.3);
+        under_test.velocity_in_world = Vector3::new(30.0, 0.0, 0.0);
+        let state = under_test.state();
+        assert!((state.heading - 0.3).abs() < 1e-9);
+        assert!((state.pitch - 0.1).abs() < 1e-9);
+        // nose right of the flight path, air comes from the left
+        assert!(state.beta < -0.25);
+        // nose above the flight path
+        assert!(state.alpha > 0.09);
+        assert!((state.airspeed - 30.0).abs() < 1e-9);
+        assert!((state.mach - 30.0 / Atmosphere::sea_level().speed_of_sound).abs() < 1e-9);
+        assert_eq!(state.velocity_ned, Vector3::new(30.0, 0.0, 0.0));
+    }
+
+    /// lift and drag from everything at a speed and alpha, level wings
+    fn lift_and_drag(plane: &Plane, speed: f64, alpha: f64) -> (f64, f64) {
+        let velocity = Vector3::new(alpha.cos(), 0.0, alpha.sin()) * speed;
+        let forces: Vector3<f64> = plane
+            .components
+            .iter()
+            .map(|component| component.loads(&Airflow::uniform(velocity)).0)
+            .sum();
+        let lift_direction = Vector3::new(alpha.sin(), 0.0, -alpha.cos());
+        (forces.dot(&lift_direction), -forces.dot(&velocity) / speed)
+    }
+
+    /// drag in level flight, alpha found by bisection so the lift holds the weight up
+    fn level_flight_drag(plane: &Plane, speed: f64) -> f64 {
+        let weight = plane.mass_properties.mass * 9.81;
+        let (mut low, mut high) = (-0.1, 0.2);
+        for _ in 0..60 {
+            let alpha = 0.5 * (low + high);
+            if lift_and_drag(plane, speed, alpha).0 > weight {
+                high = alpha;
+            } else {
+                low = alpha;
+            }
+        }
+        lift_and_drag(plane, speed, low).1
+    }
+
+    #[test]
+    fn performance_close_to_a_172() {
+        let under_test = Plane::new_solid_guess();
+        let weight = under_test.mass_properties.mass * 9.81;
+        let speeds: Vec<f64> = (30..90).map(|speed| speed as f64).collect();
+        let best_glide = speeds
+            .iter()
+            .map(|&speed| weight / level_flight_drag(&under_test, speed))
+            .fold(0.0, f64::max);
+        // published glide ratio is about 9
+        assert!(best_glide > 8.0 && best_glide < 11.0);
+        let top_speed = speeds
+            .iter()
+            .find(|&&speed| level_flight_drag(&under_test, speed) > under_test.engine.max_thrust)
+            .unwrap();
+        // about 125 knots
+        assert!(*top_speed > 58.0 && *top_speed < 72.0);
+    }
+
+    fn moments_at(plane: &Plane, alpha: f64, beta: f64) -> Vector3<f64> {
+        let mut state = plane.rigid_body_state();
+        state.transform.rotation = UnitQuaternion::identity();
+        state.velocity = Vector3::new(alpha.cos() * beta.cos(), beta.sin(), alpha.sin()) * 50.0;
+        plane
+            .calculate_all_forces(&state, &Cockpit::new(), 0.0025)
+            .1
+    }
+
+    /// how much the moments change from neutral with some controls in
+    fn control_moments(set: fn(&mut Cockpit)) -> Vector3<f64> {
+        let mut under_test = Plane::new_in_flight();
+        let neutral = moments_at(&under_test, 0.05, 0.0);
+        let mut controls = Cockpit::new();
+        set(&mut controls);
+        for component in &mut under_test.components {
+            component.update_controls(&controls);
+        }
+        moments_at(&under_test, 0.05, 0.0) - neutral
+    }
+
+    #[test]
+    fn controls_move_the_nose_the_right_way() {
+        // pulling back pitches up
+        let pull = control_moments(|controls| controls.elevator = 1.0);
+        assert!(pull.y > 1000.0);
+        // rolling left is negative about x
+        let roll = control_moments(|controls| controls.roll = 1.0);
+        assert!(roll.x < -1000.0);
+        assert!(roll.y.abs() < 0.1 * roll.x.abs());
+        // left rudder yaws left
+        let yaw = control_moments(|controls| controls.yaw = 1.0);
+        assert!(yaw.z < -500.0);
+    }
+
+    #[test]
+    fn statically_stable_in_pitch_and_yaw() {
+        let under_test = Plane::new_in_flight();
+        let nose_up = moments_at(&under_test, 0.1, 0.0).y - moments_at(&under_test, 0.0, 0.0).y;
+        assert!(nose_up < 0.0);
+        // air from the right swings the nose right, into the wind
+        assert!(moments_at(&under_test, 0.05, 0.1).z > 0.0);
+    }
+
+    #[test]
+    fn downwash_softens_the_tail() {
+        let mut under_test = Plane::new_in_flight();
+        let stiffness =
+            |plane: &Plane| moments_at(plane, 0.1, 0.0).y - moments_at(plane, 0.0, 0.0).y;
+        let with_downwash = stiffness(&under_test);
+        under_test.downwash = Downwash::none();
+        let clean_air = stiffness(&under_test);
+        assert!(with_downwash < 0.0);
+        assert!(with_downwash > 0.8 * clean_air);
+    }
+
+    #[test]
+    fn washout_turns_the_downwash_up_at_zero_alpha() {
+        let under_test = Plane::solid_guess_downwash();
+        // the tips are twisted down, so the wing pushes down a little with the body level
+        assert!(under_test.epsilon0 < 0.0);
+        assert!(under_test.epsilon0 > -1.0_f64.to_radians());
+    }
+
+    /// slowest speed the wings can hold the weight up at
+    fn stall_speed(plane: &Plane) -> f64 {
+        let weight = plane.mass_properties.mass * 9.81;
+        (15..60)
+            .map(|speed| speed as f64)
+            .find(|&speed| (0..40).any(|i| lift_and_drag(plane, speed, i as f64 * 0.01).0 > weight))
+            .unwrap()
+    }
+
+    #[test]
+    fn flaps_come_out_slowly_and_lower_the_stall_speed() {
+        let mut under_test = Plane::new_in_flight();
+        let clean = stall_speed(&under_test);
+        let mut controls = Cockpit::new();
+        controls.flaps = 3;
+        for component in &mut under_test.components {
+            component.update_controls(&controls);
+            component.move_actuators(2.0);
+        }
+        let flap = under_test.wings()[0].flap().unwrap().deflection();
+        assert!((flap - 7.5_f64.to_radians()).abs() < 1e-9);
+        for component in &mut under_test.components {
+            component.move_actuators(10.0);
+        }
+        let full_flap = stall_speed(&under_test);
+        // a 172 stalls at about 48 knots clean and 41 with full flap
+        assert!(clean > 22.0 && clean < 30.0, "{clean}");
+        assert!(full_flap < clean - 1.0, "{full_flap}");
+    }
+
+    #[test]
+    fn solid_guess_mass_properties() {
+        let under_test = Plane::new_solid_guess();
+        let mass_properties = under_test.mass_properties();
+        assert!((mass_properties.mass - 1160.0).abs() < 1e-9);
+        // cg ahead of the tail and near the main wing
+        assert!(mass_properties.cg.x > -0.5 && mass_properties.cg.x < 0.5);
+        assert!(mass_properties.cg.y.abs() < 0.1);
+        for i in 0..3 {
+            assert!(mass_properties.inertia[(i, i)] > 500.0);
+        }
+    }
 }
@@ -0,0 +1,196 @@
+use nalgebra::Vector3;
+
+use crate::wing::{Root, Strip};
+
+/// the shape of a straight tapered wing panel seen from above, plus how it's rigged
+/// wing coordinates have the root quarter chord at the origin, x forwards, y right, z down
+/// lengths along the span are measured along the panel, so dihedral doesn't shorten them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Planform {
+    pub root: Root,
+    /// root to tip for a half, tip to tip for a whole wing, m
+    pub span: f64,
+    /// m
+    pub root_chord: f64,
+    /// m
+    pub tip_chord: f64,
+    /// of the quarter chord line, positive swept back, rad
+    pub sweep: f64,
+    /// tip twisted nose down from the root, rad
+    pub washout: f64,
+    /// tips up, rad
+    pub dihedral: f64,
+    /// root chord nose up from the body x axis, rad
+    pub incidence: f64,
+}
+
+impl Planform {
+    /// straight, flat and untwisted to start with
+    pub fn new(root: Root, span: f64, root_chord: f64, tip_chord: f64) -> Planform {
+        Planform {
+            root,
+            span,
+            root_chord,
+            tip_chord,
+            sweep: 0.0,
+            washout: 0.0,
+            dihedral: 0.0,
+            incidence: 0.0,
+        }
+    }
+
+    pub fn with_sweep(mut self, sweep: f64) -> Planform {
+        self.sweep = sweep;
+        self
+    }
+
+    pub fn with_washout(mut self, washout: f64) -> Planform {
+        self.washout = washout;
+        self
+    }
+
+    pub fn with_dihedral(mut self, dihedral: f64) -> Planform {
+        self.dihedral = dihedral;
+        self
+    }
+
+    pub fn with_incidence(mut self, incidence: f64) -> Planform {
+        self.incidence = incidence;
+        self
+    }
+
+    /// root to one tip, m
+    pub fn semi_span(&self) -> f64 {
+        match self.root {
+            Root::Centre => 0.5 * self.span,
+            Root::LeftEnd | Root::RightEnd => self.span,
+        }
+    }
+
+    pub fn taper_ratio(&self) -> f64 {
+        self.tip_chord / self.root_chord
+    }
+
+    /// of this panel, m^2
+    pub fn area(&self) -> f64 {
+        0.5 * self.span * (self.root_chord + self.tip_chord)
+    }
+
+    /// of the whole wing, both halves together for a half
+    pub fn aspect_ratio(&self) -> f64 {
+        let semi_span = self.semi_span();
+        let semi_area = 0.5 * semi_span * (self.root_chord + self.tip_chord);
+        2.0 * semi_span.powi(2) / semi_area
+    }
+
+    /// the chord a rectangular wing would need to have the same area and pitching moment, m
+    pub fn mean_aerodynamic_chord(&self) -> f64 {
+        let taper = self.taper_ratio();
+        2.0 / 3.0 * self.root_chord * (1.0 + taper + taper.powi(2)) / (1.0 + taper)
+    }
+
+    /// how far out from the root the mean aerodynamic chord sits, m
+    pub fn mean_aerodynamic_chord_distance(&self) -> f64 {
+        let taper = self.taper_ratio();
+        self.semi_span() / 3.0 * (1.0 + 2.0 * taper) / (1.0 + taper)
+    }
+
+    /// quarter chord of the mean aerodynamic chord, wing coordinates
+    /// a whole wing's is on the centreline
+    pub fn aerodynamic_centre(&self) -> Vector3<f64> {
+        let distance = self.mean_aerodynamic_chord_distance();
+        let side = match self.root {
+            Root::Centre => 0.0,
+            Root::RightEnd => -1.0,
+            Root::LeftEnd => 1.0,
+        };
+        Vector3::new(
+            -distance * self.sweep.tan(),
+            side * distance * self.dihedral.cos(),
+            -distance * self.dihedral.sin(),
+        )
+    }
+
+    /// strips across each side of the root, running from left to right
+    pub fn strips(&self, count_per_side: usize) -> Vec<Strip> {
+        let semi_span = self.semi_span();
+        let width = semi_span / count_per_side as f64;
+        let side = |side: f64| {
+            (0..count_per_side).map(move |i| {
+                let out = (i as f64 + 0.5) / count_per_side as f64;
+                let distance = out * semi_span;
+                let chord = self.root_chord + (self.tip_chord - self.root_chord) * out;
+                Strip {
+                    offset: Vector3::new(
+                        -distance * self.sweep.tan(),
+                        side * distance * self.dihedral.cos(),
+                        -distance * self.dihedral.sin(),
+                    ),
+                    chord,
+                    area: chord * width,
+                    twist: self.incidence - self.washout * out,
+                    // the right tip going up is a roll to the left
+                    roll: -side * self.dihedral,
+                }
+            })
+        };
+        match self.root {
+            Root::Centre => side(-1.0).rev().chain(side(1.0)).collect(),
+            Root::RightEnd => side(-1.0).rev().collect(),
+            Root::LeftEnd => side(1.0).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+
+    use crate::{planform::Planform, wing::Root};
+
+    #[test]
+    fn rectangular_wing_is_simple() {
+        let under_test = Planform::new(Root::Centre, 10.0, 1.5, 1.5);
+        assert_eq!(under_test.area(), 15.0);
+        assert!((under_test.aspect_ratio() - 10.0 / 1.5).abs() < 1e-12);
+        assert_eq!(under_test.mean_aerodynamic_chord(), 1.5);
+        assert!((under_test.mean_aerodynamic_chord_distance() - 2.5).abs() < 1e-12);
+        assert_eq!(under_test.aerodynamic_centre(), Vector3::zeros());
+    }
+
+    #[test]
+    fn tapered_swept_half_wing() {
+        // about a 172's right wing, with some sweep to move the aerodynamic centre back
+        let under_test = Planform::new(Root::LeftEnd, 5.5, 1.63, 1.12)
+            .with_sweep(10.0_f64.to_radians())
+            .with_dihedral(1.7_f64.to_radians());
+        let mac = under_test.mean_aerodynamic_chord();
+        assert!(mac > 1.12 && mac < 1.63);
+        let centre = under_test.aerodynamic_centre();
+        assert!(centre.x < 0.0 && centre.y > 0.0 && centre.z < 0.0);
+        // whole wing aspect ratio from both halves
+        let whole = Planform::new(Root::Centre, 11.0, 1.63, 1.12);
+        assert!((under_test.aspect_ratio() - whole.aspect_ratio()).abs() < 1e-12);
+        assert!((2.0 * under_test.area() - whole.area()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn strips_add_up_and_follow_the_panel() {
+        let under_test = Planform::new(Root::Centre, 10.0, 2.0, 1.0)
+            .with_washout(0.05)
+            .with_incidence(0.02)
+            .with_dihedral(0.1);
+        let strips = under_test.strips(5);
+        assert_eq!(strips.len(), 10);
+        let area: f64 = strips.iter().map(|strip| strip.area).sum();
+        assert!((area - under_test.area()).abs() < 1e-12);
+        // left to right, tips up and twisted down
+        assert!(strips[0].offset.y < 0.0 && strips[9].offset.y > 0.0);
+        assert!(strips[0].offset.z < strips[4].offset.z);
+        assert!(strips[0].twist < strips[4].twist && strips[4].twist < 0.02);
+        assert_eq!(strips[0].roll, -strips[9].roll);
+        let left_half = Planform::new(Root::RightEnd, 5.0, 2.0, 1.0).strips(5);
+        assert!(left_half[0].offset.y < left_half[4].offset.y);
+        assert!(left_half[0].chord < left_half[4].chord);
+    }
+}
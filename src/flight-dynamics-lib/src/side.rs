@@ -0,0 +1,6 @@
+/// left or right of the plane's centreline
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
@@ -0,0 +1,86 @@
+use nalgebra::{UnitQuaternion, Vector3};
+
+use crate::earth::Geodetic;
+
+/// standard gravity, for turning accelerations into g
+pub const STANDARD_GRAVITY: f64 = 9.80665;
+
+/// a copy of everything worth knowing about the plane at one instant
+/// world vectors are north east down, body vectors are x forwards, y right, z down
+/// angles are radians
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlaneState {
+    /// seconds of physics run so far
+    pub time: f64,
+    /// cg position from the earth model's origin, m
+    pub position: Vector3<f64>,
+    pub geodetic: Geodetic,
+    /// height of the cg above the ground, m
+    pub height_above_ground: f64,
+    /// m/s
+    pub velocity_ned: Vector3<f64>,
+    /// m/s
+    pub velocity_body: Vector3<f64>,
+    pub attitude: UnitQuaternion<f64>,
+    /// right wing down is positive
+    pub roll: f64,
+    /// nose up is positive
+    pub pitch: f64,
+    /// clockwise from north
+    pub heading: f64,
+    /// p, q, r in rad/s
+    pub body_rates: Vector3<f64>,
+    /// including gravity, m/s^2
+    pub acceleration_ned: Vector3<f64>,
+    /// rad/s^2
+    pub angular_acceleration: Vector3<f64>,
+    /// what an accelerometer at the cg reads, m/s^2
+    pub specific_force_body: Vector3<f64>,
+    /// g pulled along the body z axis, 1 in level flight or parked
+    pub load_factor: f64,
+    pub alpha: f64,
+    pub beta: f64,
+    /// m/s
+    pub airspeed: f64,
+    pub mach: f64,
+}
+
+/// angle of attack, positive with the air coming from below the nose
+pub fn alpha(velocity_body: &Vector3<f64>) -> f64 {
+    if velocity_body.x == 0.0 && velocity_body.z == 0.0 {
+        return 0.0;
+    }
+    velocity_body.z.atan2(velocity_body.x)
+}
+
+/// sideslip, positive with the air coming from the right
+pub fn beta(velocity_body: &Vector3<f64>) -> f64 {
+    let speed = velocity_body.norm();
+    if speed == 0.0 {
+        return 0.0;
+    }
+    (velocity_body.y / speed).clamp(-1.0, 1.0).asin()
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+
+    use crate::state::{alpha, beta};
+
+    #[test]
+    fn alpha_and_beta_from_body_velocity() {
+        let velocity = Vector3::new(10.0, 0.0, 10.0);
+        assert!((alpha(&velocity) - std::f64::consts::FRAC_PI_4).abs() < 1e-12);
+        assert_eq!(beta(&velocity), 0.0);
+        let velocity = Vector3::new(10.0, -10.0, 0.0);
+        assert_eq!(alpha(&velocity), 0.0);
+        assert!((beta(&velocity) + std::f64::consts::FRAC_PI_4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn standing_still_has_no_angles() {
+        assert_eq!(alpha(&Vector3::zeros()), 0.0);
+        assert_eq!(beta(&Vector3::zeros()), 0.0);
+    }
+}
@@ -0,0 +1,97 @@
+use nalgebra::Isometry3;
+
+/// turns whatever the frame time happens to be into a whole number of fixed physics steps,
+/// so the flight doesn't depend on the display refresh rate
+pub struct FixedTimestep {
+    /// seconds per physics step
+    pub step: f64,
+    /// a long hitch drops the extra time instead of running hundreds of steps in one frame
+    pub max_substeps: usize,
+    accumulator: f64,
+}
+
+impl FixedTimestep {
+    pub fn new(rate_hz: f64, max_substeps: usize) -> FixedTimestep {
+        FixedTimestep {
+            step: 1.0 / rate_hz,
+            max_substeps,
+            accumulator: 0.0,
+        }
+    }
+
+    /// add on a frame's worth of time, returns how many physics steps should be run now
+    pub fn advance(&mut self, frame_dt: f64) -> usize {
+        self.accumulator += frame_dt.max(0.0);
+        // tiny tolerance so frame times that should add up to a whole step do
+        let steps = ((self.accumulator / self.step) + 1e-9).floor() as usize;
+        let steps_run = steps.min(self.max_substeps);
+        if steps > self.max_substeps {
+            self.accumulator = 0.0;
+        } else {
+            self.accumulator = (self.accumulator - steps_run as f64 * self.step).max(0.0);
+        }
+        steps_run
+    }
+
+    /// how far the frame is between the last two physics states, 0 is the previous state and 1 the current
+    pub fn alpha(&self) -> f64 {
+        (self.accumulator / self.step).clamp(0.0, 1.0)
+    }
+}
+
+/// the pose to draw, blended between the last two physics steps
+pub fn interpolate(
+    previous: &Isometry3<f64>,
+    current: &Isometry3<f64>,
+    alpha: f64,
+) -> Isometry3<f64> {
+    previous.lerp_slerp(current, alpha)
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use nalgebra::{Isometry3, Vector3};
+
+    use crate::timestep::{FixedTimestep, interpolate};
+
+    #[test]
+    fn same_steps_whatever_the_frame_rate() {
+        for frame_rate in [30.0, 60.0, 144.0, 240.0] {
+            let mut under_test = FixedTimestep::new(240.0, 20);
+            let frames = frame_rate as usize;
+            let steps: usize = (0..frames)
+                .map(|_| under_test.advance(1.0 / frame_rate))
+                .sum();
+            assert_eq!(steps, 240);
+        }
+    }
+
+    #[test]
+    fn hitch_is_clamped() {
+        let mut under_test = FixedTimestep::new(240.0, 8);
+        assert_eq!(under_test.advance(2.0), 8);
+        assert_eq!(under_test.alpha(), 0.0);
+        assert_eq!(under_test.advance(1.0 / 240.0), 1);
+    }
+
+    #[test]
+    fn leftover_time_gives_alpha() {
+        let mut under_test = FixedTimestep::new(100.0, 8);
+        assert_eq!(under_test.advance(0.025), 2);
+        assert!((under_test.alpha() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn interpolates_position_and_rotation() {
+        let previous = Isometry3::new(Vector3::new(0.0, 0.0, 0.0), Vector3::zeros());
+        let current = Isometry3::new(
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(PI / 2.0, 0.0, 0.0),
+        );
+        let halfway = interpolate(&previous, &current, 0.5);
+        assert!((halfway.translation.x - 1.0).abs() < 1e-9);
+        assert!((halfway.rotation.angle() - PI / 4.0).abs() < 1e-9);
+    }
+}
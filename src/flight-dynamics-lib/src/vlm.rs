@@ -0,0 +1,362 @@
+use std::f64::consts::PI;
+
+use nalgebra::{DMatrix, DVector, UnitQuaternion, Vector3};
+
+use crate::{atmosphere::Atmosphere, wing::Wing};
+
+/// speed the lattice is solved at, m/s
+const SPEED: f64 = 50.0;
+/// how far back the trailing legs go before they stop, m
+const WAKE_LENGTH: f64 = 1e5;
+/// nudge for the finite differences, rad or non dimensional rate
+const STEP: f64 = 1e-3;
+
+/// what the coefficients are divided by
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reference {
+    /// m^2
+    pub area: f64,
+    /// for pitch, m
+    pub chord: f64,
+    /// for roll and yaw, m
+    pub span: f64,
+}
+
+/// one horseshoe vortex, the bound leg along the quarter chord and two legs trailing back to infinity
+/// everything in body coordinates relative to the datum
+struct Panel {
+    /// left end of the bound leg as the surface sees it
+    a: Vector3<f64>,
+    b: Vector3<f64>,
+    /// three quarter chord, where the air has to run along the surface
+    control_point: Vector3<f64>,
+    normal: Vector3<f64>,
+    chord: f64,
+}
+
+/// the lift across the span at one panel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpanLoad {
+    /// middle of the bound leg, body coordinates relative to the datum
+    pub position: Vector3<f64>,
+    pub chord: f64,
+    /// section lift coefficient
+    pub cl: f64,
+}
+
+/// what one solve of the lattice gives
+pub struct Solution {
+    /// body coordinates, N
+    pub forces: Vector3<f64>,
+    /// about the cg, body coordinates, Nm
+    pub moments: Vector3<f64>,
+    pub span_loading: Vec<SpanLoad>,
+}
+
+/// non dimensional derivatives per radian, body axes about the cg
+/// rates are non dimensionalised as p b / 2V, q c / 2V and r b / 2V
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Derivatives {
+    pub CL_alpha: f64,
+    pub Cm_alpha: f64,
+    pub CY_beta: f64,
+    /// rolling moment with sideslip
+    pub Cl_beta: f64,
+    pub Cn_beta: f64,
+    pub Cl_p: f64,
+    pub Cm_q: f64,
+    pub Cn_r: f64,
+}
+
+/// a vortex lattice for the plane's surfaces, one panel deep and split up across the span
+/// only sees the geometry, no stall, no profile drag and no control deflections
+pub struct Lattice {
+    panels: Vec<Panel>,
+    cg: Vector3<f64>,
+    reference: Reference,
+    /// kg/m^3, the coefficients don't depend on it
+    density: f64,
+}
+
+impl Lattice {
+    /// every strip of every wing is cut into this many panels across the span
+    pub fn from_wings(
+        wings: &[&Wing],
+        cg: Vector3<f64>,
+        reference: Reference,
+        subdivisions: usize,
+    ) -> Lattice {
+        let mut panels = Vec::new();
+        for wing in wings {
+            let mounting = wing.transform_on_plane.translation.vector;
+            let rotation = wing.transform_on_plane.rotation;
+            let to_body = |point: Vector3<f64>| mounting + rotation * point;
+            for strip in wing.strips() {
+                let width = strip.area / strip.chord;
+                // a dihedral strip is tilted about its own middle
+                let roll = UnitQuaternion::from_euler_angles(strip.roll, 0.0, 0.0);
+                let on_strip = |point: Vector3<f64>| to_body(strip.offset + roll * point);
+                let normal =
+                    rotation * roll * Vector3::new(strip.twist.sin(), 0.0, strip.twist.cos());
+                for i in 0..subdivisions {
+                    let y0 = -0.5 * width + width * i as f64 / subdivisions as f64;
+                    let y1 = y0 + width / subdivisions as f64;
+                    panels.push(Panel {
+                        a: on_strip(Vector3::new(0.0, y0, 0.0)),
+                        b: on_strip(Vector3::new(0.0, y1, 0.0)),
+                        control_point: on_strip(Vector3::new(
+                            -0.5 * strip.chord,
+                            0.5 * (y0 + y1),
+                            0.0,
+                        )),
+                        normal,
+                        chord: strip.chord,
+                    });
+                }
+            }
+        }
+        Lattice {
+            panels,
+            cg,
+            reference,
+            density: Atmosphere::sea_level().density,
+        }
+    }
+
+    /// velocity of the plane through the air and its rotation, both body coordinates
+    /// none if the panels can't be told apart, like two wings in the same place
+    pub fn solve(
+        &self,
+        velocity: Vector3<f64>,
+        angular_velocity: Vector3<f64>,
+    ) -> Option<Solution> {
+        let air_at =
+            |point: &Vector3<f64>| -(velocity + angular_velocity.cross(&(point - self.cg)));
+
+        // no air through the surface at any control point
+        let n = self.panels.len();
+        let influence = DMatrix::from_fn(n, n, |i, j| {
+            horseshoe(&self.panels[j], &self.panels[i].control_point).dot(&self.panels[i].normal)
+        });
+        let free_air = DVector::from_fn(n, |i, _| {
+            -air_at(&self.panels[i].control_point).dot(&self.panels[i].normal)
+        });
+        let circulation = influence.lu().solve(&free_air)?;
+
+        // kutta joukowski on each bound leg
+        let speed = velocity.norm();
+        let mut forces = Vector3::zeros();
+        let mut moments = Vector3::zeros();
+        let mut span_loading = Vec::with_capacity(n);
+        for (panel, gamma) in self.panels.iter().zip(circulation.iter()) {
+            let middle = 0.5 * (panel.a + panel.b);
+            let force = self.density * gamma * air_at(&middle).cross(&(panel.b - panel.a));
+            forces += force;
+            moments += (middle - self.cg).cross(&force);
+            span_loading.push(SpanLoad {
+                position: middle,
+                chord: panel.chord,
+                cl: 2.0 * gamma / (speed * panel.chord),
+            });
+        }
+        Some(Solution {
+            forces,
+            moments,
+            span_loading,
+        })
+    }
+
+    /// lift, side force, then rolling, pitching and yawing moment coefficients
+    fn coefficients(&self, alpha: f64, beta: f64, rates: Vector3<f64>) -> Option<[f64; 5]> {
+        let reference = &self.reference;
+        let velocity = Vector3::new(
+            alpha.cos() * beta.cos(),
+            beta.sin(),
+            alpha.sin() * beta.cos(),
+        ) * SPEED;
+        let angular_velocity = Vector3::new(
+            rates.x * 2.0 * SPEED / reference.span,
+            rates.y * 2.0 * SPEED / reference.chord,
+            rates.z * 2.0 * SPEED / reference.span,
+        );
+        let solution = self.solve(velocity, angular_velocity)?;
+        let force_scale = 0.5 * self.density * SPEED.powi(2) * reference.area;
+        // lift is square to the air in the plane of symmetry
+        let lift = solution
+            .forces
+            .dot(&Vector3::new(alpha.sin(), 0.0, -alpha.cos()));
+        Some([
+            lift / force_scale,
+            solution.forces.y / force_scale,
+            solution.moments.x / (force_scale * reference.span),
+            solution.moments.y / (force_scale * reference.chord),
+            solution.moments.z / (force_scale * reference.span),
+        ])
+    }
+
+    /// central differences about some alpha with the wings level and no sideslip
+    /// none if the lattice can't be solved
+    #[allow(non_snake_case)]
+    pub fn derivatives(&self, alpha: f64) -> Option<Derivatives> {
+        let difference = |alpha_step: f64, beta_step: f64, rates: Vector3<f64>| {
+            let plus = self.coefficients(alpha + alpha_step, beta_step, rates)?;
+            let minus = self.coefficients(alpha - alpha_step, -beta_step, -rates)?;
+            Some(std::array::from_fn::<f64, 5, _>(|i| {
+                (plus[i] - minus[i]) / (2.0 * STEP)
+            }))
+        };
+        let [CL_alpha, _, _, Cm_alpha, _] = difference(STEP, 0.0, Vector3::zeros())?;
+        let [_, CY_beta, Cl_beta, _, Cn_beta] = difference(0.0, STEP, Vector3::zeros())?;
+        let Cl_p = difference(0.0, 0.0, Vector3::new(STEP, 0.0, 0.0))?[2];
+        let Cm_q = difference(0.0, 0.0, Vector3::new(0.0, STEP, 0.0))?[3];
+        let Cn_r = difference(0.0, 0.0, Vector3::new(0.0, 0.0, STEP))?[4];
+        Some(Derivatives {
+            CL_alpha,
+            Cm_alpha,
+            CY_beta,
+            Cl_beta,
+            Cn_beta,
+            Cl_p,
+            Cm_q,
+            Cn_r,
+        })
+    }
+}
+
+/// velocity a horseshoe with unit circulation induces at a point
+fn horseshoe(panel: &Panel, point: &Vector3<f64>) -> Vector3<f64> {
+    let wake = Vector3::new(-WAKE_LENGTH, 0.0, 0.0);
+    segment(&(panel.a + wake), &panel.a, point)
+        + segment(&panel.a, &panel.b, point)
+        + segment(&panel.b, &(panel.b + wake), point)
+}
+
+/// biot savart for a straight vortex segment with unit circulation, nothing right on the line
+fn segment(start: &Vector3<f64>, end: &Vector3<f64>, point: &Vector3<f64>) -> Vector3<f64> {
+    let r1 = point - start;
+    let r2 = point - end;
+    let cross = r1.cross(&r2);
+    let cross_squared = cross.norm_squared();
+    if cross_squared < 1e-12 || r1.norm() < 1e-9 || r2.norm() < 1e-9 {
+        return Vector3::zeros();
+    }
+    let along = (end - start).dot(&(r1 / r1.norm() - r2 / r2.norm()));
+    cross * along / (4.0 * PI * cross_squared)
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+
+    use crate::{
+        airflow::Airflow,
+        plane::Plane,
+        planform::Planform,
+        vlm::{Lattice, Reference},
+        wing::{Root, Wing},
+    };
+
+    fn rectangular(aspect_ratio: f64) -> Lattice {
+        let wing = Wing::new_area_location(16.0, Vector3::zeros())
+            .with_aspect_ratio(aspect_ratio)
+            .with_strips(1, 1.0, 0.0, Root::Centre);
+        let chord = wing.mean_chord();
+        let reference = Reference {
+            area: 16.0,
+            chord,
+            span: 16.0 / chord,
+        };
+        Lattice::from_wings(&[&wing], Vector3::zeros(), reference, 40)
+    }
+
+    #[test]
+    fn lift_slope_of_a_plain_wing() {
+        // lifting line gives about 4.9 for aspect ratio 7, the lattice comes in a bit under
+        let slope = rectangular(7.0).derivatives(0.0).unwrap().CL_alpha;
+        assert!(slope > 4.3 && slope < 5.0, "{slope}");
+        // stubby wings lift less
+        assert!(rectangular(2.0).derivatives(0.0).unwrap().CL_alpha < 0.7 * slope);
+    }
+
+    #[test]
+    fn loading_falls_off_at_the_tips() {
+        let loading = rectangular(7.0)
+            .solve(Vector3::new(50.0, 0.0, 5.0), Vector3::zeros())
+            .unwrap()
+            .span_loading;
+        let middle = loading[20].cl;
+        assert!(middle > 0.0);
+        assert!(loading[0].cl < 0.8 * middle);
+        assert!((loading[0].cl - loading[39].cl).abs() < 1e-9);
+    }
+
+    #[test]
+    fn same_wing_twice_has_no_solution() {
+        let wing = Wing::new_area_location(16.0, Vector3::zeros()).with_aspect_ratio(7.0);
+        let reference = Reference {
+            area: 16.0,
+            chord: wing.mean_chord(),
+            span: 16.0 / wing.mean_chord(),
+        };
+        let under_test = Lattice::from_wings(&[&wing, &wing], Vector3::zeros(), reference, 4);
+        assert!(
+            under_test
+                .solve(Vector3::new(50.0, 0.0, 5.0), Vector3::zeros())
+                .is_none()
+        );
+        assert!(under_test.derivatives(0.0).is_none());
+    }
+
+    #[test]
+    fn dihedral_gives_roll_stability() {
+        let roll_with_slip = |dihedral: f64| {
+            let planform =
+                Planform::new(Root::Centre, 10.0, 1.6, 1.6).with_dihedral(dihedral.to_radians());
+            let wing = Wing::from_planform(planform, Vector3::zeros(), 4);
+            let reference = Reference {
+                area: planform.area(),
+                chord: planform.mean_aerodynamic_chord(),
+                span: planform.span,
+            };
+            Lattice::from_wings(&[&wing], Vector3::zeros(), reference, 10)
+                .derivatives(0.05)
+                .unwrap()
+                .Cl_beta
+        };
+        assert!(roll_with_slip(0.0).abs() < 1e-6);
+        assert!(roll_with_slip(5.0) < -0.01);
+    }
+
+    #[test]
+    fn solid_guess_is_stable() {
+        let plane = Plane::new_in_flight();
+        let reference = Reference {
+            area: 17.16,
+            chord: (17.16_f64 / 7.0).sqrt(),
+            span: (17.16_f64 * 7.0).sqrt(),
+        };
+        let lattice = Lattice::from_wings(&plane.wings(), plane.mass_properties().cg, reference, 4);
+        let derivatives = lattice.derivatives(0.05).unwrap();
+        assert!(derivatives.Cm_alpha < 0.0);
+        assert!(derivatives.Cn_beta > 0.0);
+        assert!(derivatives.CY_beta < 0.0);
+        assert!(derivatives.Cl_p < 0.0);
+        assert!(derivatives.Cm_q < 0.0);
+        assert!(derivatives.Cn_r < 0.0);
+
+        // the runtime model should be in the same ballpark for lift
+        let lift = |alpha: f64| -> f64 {
+            let velocity = Vector3::new(alpha.cos(), 0.0, alpha.sin()) * 50.0;
+            let forces: Vector3<f64> = plane
+                .wings()
+                .iter()
+                .map(|wing| wing.calculate_forces(&Airflow::uniform(velocity)))
+                .sum();
+            -forces.z / (0.5 * 1.225 * 2500.0 * reference.area)
+        };
+        let runtime_slope = (lift(0.06) - lift(0.04)) / 0.02;
+        let ratio = runtime_slope / derivatives.CL_alpha;
+        assert!(ratio > 0.8 && ratio < 1.3, "{ratio}");
+    }
+}
@@ -1,72 +1,648 @@
-use std::f64::{self, consts};
+use std::{f64, sync::Arc};
 
-use nalgebra::{Isometry3, Point3, Transform3, Vector, Vector3};
+use nalgebra::{Isometry3, UnitQuaternion, Vector3};
+
+use crate::{
+    aero::AeroComponent,
+    airflow::Airflow,
+    airfoil::Airfoil,
+    cockpit::Cockpit,
+    control::ControlSurface,
+    high_lift::{Flap, Increments, Slat, Spoiler},
+    planform::Planform,
+};
+
+/// for a wing given only its area, about a light aircraft's
+const DEFAULT_ASPECT_RATIO: f64 = 7.0;
+
+/// Prandtl-Glauert runs away approaching Mach 1, hold it at this
+const PRANDTL_GLAUERT_LIMIT: f64 = 0.9;
+/// how far past critical Lock's drag rise keeps climbing
+const DRAG_RISE_LIMIT: f64 = 0.3;
+/// how far the centre of pressure slides back once the shock has formed, fraction of chord
+const MACH_TUCK: f64 = 0.15;
 
 pub struct Wing {
     area: f64,
     pub transform_on_plane: Isometry3<f64>,
     aspect_ratio: f64,
+    /// most lift the wing makes before it stalls, the same either way up
+    cl_max: f64,
+    /// how many radians past the stall the lift takes to fall away to a flat plate
+    stall_width: f64,
+    /// drag coefficient with no lift
+    cd0: f64,
+    /// 1 for an elliptical lift distribution, less for anything real
+    oswald_efficiency: f64,
+    /// profile drag against alpha in radians, sorted by alpha, used instead of cd0 when there is one
+    profile_drag: Option<Vec<(f64, f64)>>,
+    /// pitching moment coefficient about the aerodynamic centre, negative for a cambered section
+    cm0: f64,
+    /// area of the whole wing this is part of, for the chord
+    whole_area: f64,
+    control_surface: Option<ControlSurface>,
+    flap: Option<Flap>,
+    slat: Option<Slat>,
+    spoiler: Option<Spoiler>,
+    /// measured section data, replaces the lift curve, profile drag and cm0 inside the polar's alpha range
+    airfoil: Option<Arc<Airfoil>>,
+    /// none means the whole wing works as one strip at the mounting point
+    strips: Option<Vec<Strip>>,
+    /// that one strip, kept up to date with the area and aspect ratio
+    whole_strip: Strip,
+    /// sits behind the main wing and feels its downwash
+    in_downwash: bool,
+    /// where the air over the top first goes supersonic and the drag starts to climb
+    critical_mach: f64,
+    /// the shape the strips were cut from, if the wing was drawn out
+    planform: Option<Planform>,
+}
+
+/// a slice of the wing across the span, each one sees its own air and stalls on its own
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Strip {
+    /// middle of the strip from the wing's mounting point, wing coordinates
+    pub offset: Vector3<f64>,
+    pub chord: f64,
+    pub area: f64,
+    /// added to alpha, washout is negative towards the tip
+    pub twist: f64,
+    /// about the wing's x axis, for dihedral, right side down is positive
+    pub roll: f64,
+}
+
+/// which end of a panel is bolted to the fuselage
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Root {
+    /// a whole wing, root in the middle and a tip at each end
+    Centre,
+    /// the left half of a wing, its root is at its right hand end
+    RightEnd,
+    /// the right half of a wing
+    LeftEnd,
+}
+
+impl Strip {
+    /// straight taper and linear twist, the chords are scaled so the strips add up to the area
+    pub fn tapered(
+        area: f64,
+        span: f64,
+        taper_ratio: f64,
+        washout: f64,
+        count: usize,
+        root: Root,
+    ) -> Vec<Strip> {
+        let width = span / count as f64;
+        (0..count)
+            .map(|i| {
+                let y = -0.5 * span + (i as f64 + 0.5) * width;
+                // 0 at the root and 1 at the tip
+                let out = match root {
+                    Root::Centre => 2.0 * y.abs() / span,
+                    Root::RightEnd => 0.5 - y / span,
+                    Root::LeftEnd => 0.5 + y / span,
+                };
+                let shape = 1.0 - (1.0 - taper_ratio) * out;
+                let chord = shape * 2.0 * area / (span * (1.0 + taper_ratio));
+                Strip {
+                    offset: Vector3::new(0.0, y, 0.0),
+                    chord,
+                    area: chord * width,
+                    twist: -washout * out,
+                    roll: 0.0,
+                }
+            })
+            .collect()
+    }
+}
+
+/// what the wing makes at one alpha, before any control surface
+struct Coefficients {
+    lift: f64,
+    /// drag at the whole airflow's dynamic pressure
+    profile_drag: f64,
+    /// induced drag, or the flat plate once stalled
+    lift_drag: f64,
+    moment: f64,
+    stalled: f64,
 }
 
 #[allow(non_snake_case)]
 impl Wing {
     pub fn new_area_only(area: f64) -> Wing {
-        Wing {
-            area,
-            transform_on_plane: Isometry3::new(Vector3::new(0.0, 0.0, 0.0), nalgebra::zero()),
-            aspect_ratio: 5.0,
-        }
+        Wing::new_area_location(area, Vector3::zeros())
     }
 
     pub fn new_area_location(area: f64, location_on_plane: Vector3<f64>) -> Wing {
+        let chord = (area / DEFAULT_ASPECT_RATIO).sqrt();
         Wing {
             area,
             transform_on_plane: Isometry3::new(location_on_plane, nalgebra::zero()),
-            aspect_ratio: 7.0,
+            aspect_ratio: DEFAULT_ASPECT_RATIO,
+            cl_max: 1.4,
+            stall_width: 8.0_f64.to_radians(),
+            cd0: 0.01,
+            oswald_efficiency: 0.8,
+            profile_drag: None,
+            cm0: 0.0,
+            whole_area: area,
+            control_surface: None,
+            flap: None,
+            slat: None,
+            spoiler: None,
+            airfoil: None,
+            strips: None,
+            whole_strip: Strip {
+                offset: Vector3::zeros(),
+                chord,
+                area,
+                twist: 0.0,
+                roll: 0.0,
+            },
+            in_downwash: false,
+            critical_mach: 0.7,
+            planform: None,
+        }
+    }
+
+    /// a wing on its side with its top at the root, lift to the right is positive
+    pub fn new_fin(area: f64, location_on_plane: Vector3<f64>) -> Wing {
+        let mut fin = Wing::new_area_location(area, location_on_plane);
+        fin.transform_on_plane.rotation =
+            UnitQuaternion::from_euler_angles(f64::consts::FRAC_PI_2, 0.0, 0.0);
+        fin
+    }
+
+    /// a wing drawn out in full, mounted with its root quarter chord at the location
+    /// the area, aspect ratio and strips all come from the planform
+    pub fn from_planform(
+        planform: Planform,
+        location_on_plane: Vector3<f64>,
+        strips_per_side: usize,
+    ) -> Wing {
+        let mut wing = Wing::new_area_location(planform.area(), location_on_plane)
+            .with_aspect_ratio(planform.aspect_ratio());
+        if planform.root != Root::Centre {
+            wing = wing.as_half();
+        }
+        wing.strips = Some(planform.strips(strips_per_side));
+        wing.planform = Some(planform);
+        wing
+    }
+
+    pub fn with_stall(mut self, cl_max: f64, stall_width: f64) -> Wing {
+        self.cl_max = cl_max;
+        self.stall_width = stall_width;
+        self
+    }
+
+    pub fn with_drag(mut self, cd0: f64, oswald_efficiency: f64) -> Wing {
+        self.cd0 = cd0;
+        self.oswald_efficiency = oswald_efficiency;
+        self
+    }
+
+    /// pairs of alpha in radians and section drag coefficient, sorted by alpha
+    pub fn with_profile_drag(mut self, table: Vec<(f64, f64)>) -> Wing {
+        self.profile_drag = Some(table);
+        self
+    }
+
+    pub fn with_aspect_ratio(mut self, aspect_ratio: f64) -> Wing {
+        self.aspect_ratio = aspect_ratio;
+        self.whole_strip.chord = self.mean_chord();
+        self
+    }
+
+    /// nose down is negative, the same as a normal cambered aerofoil
+    pub fn with_pitching_moment(mut self, cm0: f64) -> Wing {
+        self.cm0 = cm0;
+        self
+    }
+
+    /// one side of a wing split down the middle, the aspect ratio is still the whole wing's
+    pub fn as_half(mut self) -> Wing {
+        self.whole_area = 2.0 * self.area;
+        self.whole_strip.chord = self.mean_chord();
+        self
+    }
+
+    /// a tailplane behind the main wing, the air reaching it is already bent down
+    pub fn in_downwash(mut self) -> Wing {
+        self.in_downwash = true;
+        self
+    }
+
+    pub fn with_flap(mut self, flap: Flap) -> Wing {
+        self.flap = Some(flap);
+        self
+    }
+
+    pub fn with_slat(mut self, slat: Slat) -> Wing {
+        self.slat = Some(slat);
+        self
+    }
+
+    pub fn with_spoiler(mut self, spoiler: Spoiler) -> Wing {
+        self.spoiler = Some(spoiler);
+        self
+    }
+
+    pub fn with_critical_mach(mut self, critical_mach: f64) -> Wing {
+        self.critical_mach = critical_mach;
+        self
+    }
+
+    pub fn with_control_surface(mut self, control_surface: ControlSurface) -> Wing {
+        self.control_surface = Some(control_surface);
+        self
+    }
+
+    /// shared so both halves of a wing can use the same polars
+    pub fn with_airfoil(mut self, airfoil: Arc<Airfoil>) -> Wing {
+        self.airfoil = Some(airfoil);
+        self
+    }
+
+    pub fn planform(&self) -> Option<&Planform> {
+        self.planform.as_ref()
+    }
+
+    pub fn control_surface(&self) -> Option<&ControlSurface> {
+        self.control_surface.as_ref()
+    }
+
+    /// move the control surface, if there is one, to follow the cockpit
+    pub fn update_controls(&mut self, controls: &Cockpit) {
+        if let Some(control_surface) = &mut self.control_surface {
+            control_surface.update_controls(controls);
+        }
+        if let Some(flap) = &mut self.flap {
+            flap.update_controls(controls);
+        }
+        if let Some(slat) = &mut self.slat {
+            slat.update_controls(controls);
+        }
+        if let Some(spoiler) = &mut self.spoiler {
+            spoiler.update_controls(controls);
+        }
+    }
+
+    /// run the flap, slat and spoiler motors towards wherever the cockpit last asked for
+    pub fn move_actuators(&mut self, dt: f64) {
+        if let Some(flap) = &mut self.flap {
+            flap.move_actuators(dt);
+        }
+        if let Some(slat) = &mut self.slat {
+            slat.move_actuators(dt);
+        }
+        if let Some(spoiler) = &mut self.spoiler {
+            spoiler.move_actuators(dt);
+        }
+    }
+
+    pub fn flap(&self) -> Option<&Flap> {
+        self.flap.as_ref()
+    }
+
+    /// everything the flaps, slats and spoilers are doing right now, added up
+    fn high_lift(&self) -> Increments {
+        let dcl_dalpha = self.dcl_dalpha();
+        let flap = self
+            .flap
+            .as_ref()
+            .map_or(Increments::none(), |flap| flap.increments(dcl_dalpha));
+        let slat = self
+            .slat
+            .as_ref()
+            .map_or(Increments::none(), |slat| slat.increments(dcl_dalpha));
+        let spoiler = self
+            .spoiler
+            .as_ref()
+            .map_or(Increments::none(), |spoiler| spoiler.increments());
+        flap.combine(slat).combine(spoiler)
+    }
+
+    /// of this panel, m^2
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+
+    /// mean geometric chord, m
+    pub fn mean_chord(&self) -> f64 {
+        (self.whole_area / self.aspect_ratio).sqrt()
+    }
+
+    /// span of this panel, half the whole wing's for a half
+    pub fn span(&self) -> f64 {
+        self.area / self.mean_chord()
+    }
+
+    /// cut the wing into strips across the span, see [`Strip::tapered`]
+    pub fn with_strips(mut self, count: usize, taper_ratio: f64, washout: f64, root: Root) -> Wing {
+        self.strips = Some(Strip::tapered(
+            self.area,
+            self.span(),
+            taper_ratio,
+            washout,
+            count,
+            root,
+        ));
+        self
+    }
+
+    pub fn strips(&self) -> &[Strip] {
+        match &self.strips {
+            Some(strips) => strips,
+            None => std::slice::from_ref(&self.whole_strip),
         }
     }
 
-    /// take in velocity in the aircraft coordinates, transform it to be in wing coordinates, do lift and drag, transform it back to aircraft
-    /// cl and cd are crude for now
+    /// how stalled each strip is, 0 attached to 1 fully separated, in the same order as the strips
+    pub fn stalled_fractions(&self, airflow: &Airflow) -> Vec<f64> {
+        self.strips()
+            .iter()
+            .map(|strip| self.strip_loads(strip, airflow).2)
+            .collect()
+    }
+
+    /// take the air at the wing's mounting point in aircraft coordinates, transform it to be in wing coordinates, do lift and drag, transform it back to aircraft
+    /// the plane's rotation moves the wing through the air too, which is where the damping comes from
+    /// lift comes from the air across the span and is square to the wing's span and the airflow,
+    /// so a fin or a dihedral panel lifts sideways out of its own plane
+    /// drag is along the whole airflow, air running along the span only rubs on the skin
+    pub fn calculate_forces(&self, airflow: &Airflow) -> Vector3<f64> {
+        self.loads(airflow).0
+    }
+
+    /// forces and moments about the cg together, for one pass over the strips
+    pub fn loads(&self, airflow: &Airflow) -> (Vector3<f64>, Vector3<f64>) {
+        self.strips().iter().fold(
+            (Vector3::zeros(), Vector3::zeros()),
+            |(forces, moments), strip| {
+                let (strip_forces, strip_moments, _) = self.strip_loads(strip, airflow);
+                (forces + strip_forces, moments + strip_moments)
+            },
+        )
+    }
+
+    /// forces, moments and how stalled one strip is
+    /// the middle of the strip is the aerodynamic centre, where the aerofoil's own pitching moment doesn't change with alpha
+    /// once stalled the centre of pressure slides back to half chord like a flat plate
     #[allow(non_snake_case)]
-    pub fn calculate_forces(&self, velocity_body: &Vector3<f64>) -> Vector3<f64> {
-        let velocity_wing = self.transform_on_plane.rotation * velocity_body;
+    fn strip_loads(&self, strip: &Strip, airflow: &Airflow) -> (Vector3<f64>, Vector3<f64>, f64) {
+        let aerodynamic_centre = self.transform_on_plane.translation.vector
+            + self.transform_on_plane.rotation * strip.offset;
+        let rotation = self.transform_on_plane.rotation
+            * UnitQuaternion::from_euler_angles(strip.roll, 0.0, 0.0);
+        let velocity_body = airflow.velocity_at(&aerodynamic_centre);
+        let velocity_wing = rotation.inverse() * velocity_body;
         let U_inf = velocity_wing.magnitude();
-        let alpha = (velocity_wing.z / velocity_wing.x).tan().to_degrees();
+        if U_inf == 0.0 {
+            return (Vector3::zeros(), Vector3::zeros(), 0.0);
+        }
+        let (alpha, beta) = Wing::flow_angles(&velocity_wing);
+        // only the air across the span makes lift, sideslip takes cos^2 beta off the dynamic pressure
+        let across_span = U_inf * beta.cos();
+
+        // a deflected control surface acts like a change in alpha
+        let (alpha_shift, control_drag, control_moment) = match &self.control_surface {
+            Some(control_surface) => (
+                control_surface.alpha_shift(),
+                control_surface.drag(),
+                control_surface.pitching_moment(),
+            ),
+            None => (0.0, 0.0, 0.0),
+        };
+        let high_lift = self.high_lift();
+        let downwash = if self.in_downwash {
+            airflow.downwash
+        } else {
+            0.0
+        };
+        let effective_alpha = alpha - downwash + strip.twist + alpha_shift + high_lift.alpha_shift;
+
+        let chord = strip.chord;
+        let atmosphere = &airflow.atmosphere;
+        let reynolds = across_span * chord / atmosphere.kinematic_viscosity;
+        let mach = U_inf / atmosphere.speed_of_sound;
+        let mut coefficients = self.coefficients(effective_alpha, reynolds, high_lift.cl_max);
+        coefficients.lift *= 1.0 + (high_lift.lift_scale - 1.0) * (1.0 - coefficients.stalled);
+        coefficients.profile_drag += high_lift.drag;
+        let (lift_factor, induced_factor) = self.ground_effect(
+            airflow.height_at(&aerodynamic_centre),
+            airflow.attitude * rotation * Vector3::y(),
+        );
+        coefficients.lift *= 1.0 + (lift_factor - 1.0) * (1.0 - coefficients.stalled);
+        coefficients.lift_drag *= induced_factor;
+        let (compressibility, drag_rise, tuck) = self.compressibility(mach);
+        let attached = 1.0 - coefficients.stalled;
+        coefficients.lift *= 1.0 + (compressibility - 1.0) * attached;
+        coefficients.lift_drag *= 1.0 + (compressibility.powi(2) - 1.0) * attached;
+        coefficients.profile_drag += drag_rise;
+
+        let dynamic_pressure = 0.5 * atmosphere.density * U_inf.powi(2);
+        let across_span_pressure = 0.5 * atmosphere.density * across_span.powi(2);
+        let lift = across_span_pressure * strip.area * coefficients.lift;
+        let drag = across_span_pressure * strip.area * coefficients.lift_drag
+            + dynamic_pressure * strip.area * (coefficients.profile_drag + control_drag);
+
+        let drag_direction = -velocity_wing / U_inf;
+        let lift_direction = if across_span > 0.0 {
+            Vector3::y().cross(&velocity_wing) / across_span
+        } else {
+            Vector3::zeros()
+        };
+        let forces = rotation * (lift * lift_direction + drag * drag_direction);
+
+        let stalled = coefficients.stalled;
+        let aft_shift = 0.25 * stalled + MACH_TUCK * tuck;
+        let centre_of_pressure =
+            aerodynamic_centre + rotation * Vector3::new(-aft_shift * chord, 0.0, 0.0);
+        let pitching_moment = across_span_pressure
+            * strip.area
+            * chord
+            * (coefficients.moment
+                + (control_moment + high_lift.pitching_moment) * (1.0 - stalled));
+        let moments = (centre_of_pressure - airflow.cg).cross(&forces)
+            + rotation * Vector3::new(0.0, pitching_moment, 0.0);
+        (forces, moments, stalled)
+    }
+
+    /// what Mach does to the wing, returns
+    /// what to multiply the attached lift by, Prandtl-Glauert held below Mach 1,
+    /// the extra profile drag from Lock's fourth power drag rise past the critical Mach,
+    /// and 0 to 1 for how far the shock has pushed the centre of pressure back
+    pub fn compressibility(&self, mach: f64) -> (f64, f64, f64) {
+        let held = mach.min(PRANDTL_GLAUERT_LIMIT);
+        let lift = 1.0 / (1.0 - held.powi(2)).sqrt();
+        let past_critical = (mach - self.critical_mach).clamp(0.0, DRAG_RISE_LIMIT);
+        let drag_rise = 20.0 * past_critical.powi(4);
+        let t = (past_critical / 0.1).min(1.0);
+        (lift, drag_rise, t * t * (3.0 - 2.0 * t))
+    }
+
+    /// near the ground the tip vortices can't get going, McCormick's fit for the induced drag
+    /// and the lift slope of a wing with the aspect ratio that leaves
+    /// fades out as the span tips away from level, a fin gets nothing
+    /// returns what to multiply the attached lift and the induced drag by
+    pub fn ground_effect(&self, height: f64, span_in_world: Vector3<f64>) -> (f64, f64) {
+        let span = (self.whole_area * self.aspect_ratio).sqrt();
+        let closeness = 16.0 * height.max(0.0) / span;
+        let induced = 1.0 / (1.0 + closeness.powi(-2));
+        let level = 1.0 - span_in_world.z.powi(2).min(1.0);
+        let induced = 1.0 - (1.0 - induced) * level;
+        let lift = (1.0 + 2.0 / self.aspect_ratio) / (1.0 + 2.0 * induced / self.aspect_ratio);
+        (lift, induced)
+    }
+
+    /// alpha and sideslip of the air over the wing, velocity in wing coordinates
+    /// sideslip is positive with the air coming from the wing's right
+    pub fn flow_angles(velocity_wing: &Vector3<f64>) -> (f64, f64) {
+        let speed = velocity_wing.magnitude();
+        if speed == 0.0 {
+            return (0.0, 0.0);
+        }
+        let alpha = velocity_wing.z.atan2(velocity_wing.x);
+        let beta = (velocity_wing.y / speed).clamp(-1.0, 1.0).asin();
+        (alpha, beta)
+    }
+
+    /// the analytic lift curve, or the airfoil's polars when it has them
+    /// high lift devices raise cl_max on the analytic curve, a polar only sees their shift in alpha
+    fn coefficients(&self, alpha: f64, reynolds: f64, cl_max_gain: f64) -> Coefficients {
+        let Some(airfoil) = &self.airfoil else {
+            let (lift, lift_drag, stalled) = self.analytic(alpha, self.cl_max + cl_max_gain);
+            return Coefficients {
+                lift,
+                profile_drag: self.profile_drag_coefficient(alpha),
+                lift_drag,
+                moment: self.cm0 * (1.0 - stalled),
+                stalled,
+            };
+        };
+
+        // the polar is for an infinite wing, the tip vortices take some of the alpha away
+        let section_alpha = alpha * self.dcl_dalpha() / (2.0 * f64::consts::PI);
+        let (low, high) = airfoil.alpha_range();
+        let section = airfoil.coefficients(section_alpha.clamp(low, high), reynolds);
+        // past the end of the table it eases over to a flat plate
+        let past_table =
+            ((low - section_alpha).max(section_alpha - high) / self.stall_width).clamp(0.0, 1.0);
+        let stalled = past_table * past_table * (3.0 - 2.0 * past_table);
+        let induced =
+            section.cl.powi(2) / (f64::consts::PI * self.aspect_ratio * self.oswald_efficiency);
+        Coefficients {
+            lift: (1.0 - stalled) * section.cl + stalled * (2.0 * alpha).sin(),
+            profile_drag: section.cd,
+            lift_drag: (1.0 - stalled) * induced + stalled * 2.0 * alpha.sin().powi(2),
+            moment: (1.0 - stalled) * section.cm,
+            stalled,
+        }
+    }
 
-        //println!("{}, {}, {:?}", U_inf, alpha, velocity_wing);
+    /// lift, the drag that comes with it and how stalled, for some cl_max
+    /// linear up to cl_max then eases over to a flat plate across the stall width,
+    /// induced drag while the flow is attached and a flat plate's drag once stalled
+    fn analytic(&self, alpha: f64, cl_max: f64) -> (f64, f64, f64) {
+        let attached = (self.dcl_dalpha() * alpha).clamp(-cl_max, cl_max);
+        let stall_angle = cl_max / self.dcl_dalpha();
+        let past_stall = ((alpha.abs() - stall_angle) / self.stall_width).clamp(0.0, 1.0);
+        let stalled = past_stall * past_stall * (3.0 - 2.0 * past_stall);
+        let induced =
+            attached.powi(2) / (f64::consts::PI * self.aspect_ratio * self.oswald_efficiency);
+        let lift = (1.0 - stalled) * attached + stalled * (2.0 * alpha).sin();
+        let lift_drag = (1.0 - stalled) * induced + stalled * 2.0 * alpha.sin().powi(2);
+        (lift, lift_drag, stalled)
+    }
+
+    /// linear up to cl_max, then eases over to a flat plate across the stall width
+    pub fn lift_coefficient(&self, alpha: f64) -> f64 {
+        self.analytic(alpha, self.cl_max).0
+    }
+
+    /// profile drag all the way round, plus induced drag while the flow is attached and a flat plate once stalled
+    pub fn drag_coefficient(&self, alpha: f64) -> f64 {
+        self.profile_drag_coefficient(alpha) + self.analytic(alpha, self.cl_max).1
+    }
 
-        let cl = 0.1 * alpha;
-        let cd = cl.powi(2);
-        let lift_body = 0.5 * 1.225 * U_inf.powi(2) * self.area * cl;
-        let drag_body = 0.5 * 1.225 * U_inf.powi(2) * self.area * cd;
-        let forces_body = Vector3::new(-drag_body, 0.0, lift_body);
-        return self.transform_on_plane.inverse() * forces_body;
+    /// straight line between the table points, held flat off either end
+    fn profile_drag_coefficient(&self, alpha: f64) -> f64 {
+        let Some(table) = &self.profile_drag else {
+            return self.cd0;
+        };
+        match table
+            .iter()
+            .position(|&(table_alpha, _)| table_alpha > alpha)
+        {
+            None => table.last().map_or(self.cd0, |&(_, cd)| cd),
+            Some(0) => table[0].1,
+            Some(i) => {
+                let (alpha_0, cd_0) = table[i - 1];
+                let (alpha_1, cd_1) = table[i];
+                cd_0 + (cd_1 - cd_0) * (alpha - alpha_0) / (alpha_1 - alpha_0)
+            }
+        }
     }
 
-    pub fn calculate_moments(&self, velocity_body: &Vector3<f64>) -> Vector3<f64> {
-        let forces = self.calculate_forces(&velocity_body);
-        return self
-            .transform_on_plane
-            .translation
-            .vector
-            .component_mul(&forces);
+    /// about the cg
+    pub fn calculate_moments(&self, airflow: &Airflow) -> Vector3<f64> {
+        self.loads(airflow).1
     }
 
-    #[allow(dead_code)]
+    /// lift curve slope per radian, finite wings lose some to the tip vortices
+    #[allow(clippy::needless_return)]
     fn dcl_dalpha(&self) -> f64 {
         let littlefrac = 2.0 / self.aspect_ratio;
         return 2.0 * f64::consts::PI / (1.0 + littlefrac);
     }
 }
 
+impl AeroComponent for Wing {
+    /// each strip on its own, added up
+    fn loads(&self, airflow: &Airflow) -> (Vector3<f64>, Vector3<f64>) {
+        Wing::loads(self, airflow)
+    }
+
+    fn update_controls(&mut self, controls: &Cockpit) {
+        Wing::update_controls(self, controls);
+    }
+
+    fn move_actuators(&mut self, dt: f64) {
+        Wing::move_actuators(self, dt);
+    }
+
+    fn as_wing(&self) -> Option<&Wing> {
+        Some(self)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use std::f64;
+    use std::{f64, sync::Arc};
 
-    use nalgebra::{Transform3, Translation3, UnitQuaternion, Vector3};
+    use nalgebra::{Translation3, UnitQuaternion, Vector3};
 
-    use crate::wing::Wing;
+    use crate::{
+        airflow::Airflow,
+        airfoil::Airfoil,
+        atmosphere::Atmosphere,
+        cockpit::Cockpit,
+        control::{ControlSurface, Mixing},
+        high_lift::{Flap, FlapKind, Slat, Spoiler},
+        planform::Planform,
+        wing::{Root, Strip, Wing},
+    };
+
+    fn sea_level_density() -> f64 {
+        Atmosphere::sea_level().density
+    }
+
+    /// how much compressibility adds to the lift at a speed at sea level
+    fn prandtl_glauert(wing: &Wing, speed: f64) -> f64 {
+        wing.compressibility(speed / Atmosphere::sea_level().speed_of_sound)
+            .0
+    }
 
     #[test]
     #[allow(non_snake_case)]
@@ -75,27 +651,32 @@ mod test {
         under_test.transform_on_plane.translation = Translation3::new(1.0, 0.0, 0.0);
         let U_inf = Vector3::new(10.0, 0.0, 0.0);
 
-        assert_eq!(
-            under_test.calculate_forces(&U_inf),
-            Vector3::new(0.0, 0.0, 0.0)
-        );
+        let forces = under_test.calculate_forces(&Airflow::uniform(U_inf));
+        assert_eq!(forces.y, 0.0);
+        assert_eq!(forces.z, 0.0);
+        // only the parasite drag is left
+        assert!((-forces.x - 0.5 * sea_level_density() * 100.0 * 0.01).abs() < 1e-9);
 
-        let moments = under_test.calculate_moments(&U_inf);
-        assert_eq!(moments, Vector3::new(0.0, 0.0, 0.0));
+        let moments = under_test.calculate_moments(&Airflow::uniform(U_inf));
+        assert_eq!(moments.y, 0.0);
+        assert_eq!(moments.z, 0.0);
     }
 
     #[test]
     #[allow(non_snake_case)]
     fn alpha_is_1() {
-        let pi = 3.14159_f64;
-        let mut under_test = Wing::new_area_only(1.0);
-        let U_inf = Vector3::new(10.0 * (pi / 180.0).cos(), 0.0, -10.0 * (pi / 180.0).sin());
+        let pi = f64::consts::PI;
+        let under_test = Wing::new_area_only(1.0);
+        let U_inf = Vector3::new(10.0 * (pi / 180.0).cos(), 0.0, 10.0 * (pi / 180.0).sin());
 
-        let forces = under_test.calculate_forces(&U_inf);
+        let forces = under_test.calculate_forces(&Airflow::uniform(U_inf));
 
-        assert!((-forces.z - (0.5 * 1.225 * 10.0)).abs() < 1e-2);
+        let lift = 0.5 * sea_level_density() * 100.0 * under_test.dcl_dalpha() * pi / 180.0
+            * prandtl_glauert(&under_test, 10.0);
+        let lift_direction = Vector3::new((pi / 180.0).sin(), 0.0, -(pi / 180.0).cos());
+        assert!((forces.dot(&lift_direction) - lift).abs() < 1e-9);
 
-        let moments = under_test.calculate_moments(&U_inf);
+        let moments = under_test.calculate_moments(&Airflow::uniform(U_inf));
 
         assert_eq!(moments, Vector3::new(0.0, 0.0, 0.0));
     }
@@ -103,46 +684,527 @@ mod test {
     #[test]
     #[allow(non_snake_case)]
     fn setting_angle_is_1() {
-        let pi = 3.14159_f64;
+        let pi = f64::consts::PI;
         let mut under_test = Wing::new_area_only(1.0);
         under_test.transform_on_plane.rotation =
             UnitQuaternion::from_euler_angles(0.0, pi / 180.0, 0.0);
         let U_inf = Vector3::new(10.0, 0.0, 0.0);
 
-        let forces = under_test.calculate_forces(&U_inf);
-
-        println!("{:?}", forces);
+        let forces = under_test.calculate_forces(&Airflow::uniform(U_inf));
 
-        assert!((-forces.z - (0.5 * 1.225 * 10.0)).abs() < 1e-1);
+        let lift = 0.5 * sea_level_density() * 100.0 * under_test.dcl_dalpha() * pi / 180.0;
+        assert!((-forces.z - lift).abs() < 1e-1);
     }
 
     #[test]
     #[allow(non_snake_case)]
     fn setting_angle_and_alpha_are_1() {
-        let pi = 3.14159_f64;
+        let pi = f64::consts::PI;
         let mut under_test = Wing::new_area_only(1.0);
         under_test.transform_on_plane.rotation =
             UnitQuaternion::from_euler_angles(0.0, pi / 180.0, 0.0);
-        let U_inf = Vector3::new(10.0 * (pi / 180.0).cos(), 0.0, -10.0 * (pi / 180.0).sin());
+        let U_inf = Vector3::new(10.0 * (pi / 180.0).cos(), 0.0, 10.0 * (pi / 180.0).sin());
 
-        let forces = under_test.calculate_forces(&U_inf);
+        let forces = under_test.calculate_forces(&Airflow::uniform(U_inf));
 
-        assert!((-forces.z - (0.5 * 1.225 * 100.0 * 0.2)).abs() < 1e-1);
+        let lift = 0.5 * sea_level_density() * 100.0 * under_test.dcl_dalpha() * 2.0 * pi / 180.0;
+        assert!((-forces.z - lift).abs() < 1e-1);
     }
 
     #[test]
     #[allow(non_snake_case)]
-    fn rotated_wing_gives_equal_x_and_y() {
-        let pi = 3.14159_f64;
+    fn rolled_wing_lifts_equally_up_and_sideways() {
+        let pi = f64::consts::PI;
         let mut under_test = Wing::new_area_only(1.0);
         under_test.transform_on_plane.rotation =
             UnitQuaternion::from_euler_angles(45.0 * pi / 180.0, 0.0, 0.0);
         let U_inf = Vector3::new(10.0 * (pi / 180.0).cos(), 0.0, -10.0 * (pi / 180.0).sin());
 
-        let forces = under_test.calculate_forces(&U_inf);
+        let forces = under_test.calculate_forces(&Airflow::uniform(U_inf));
+        let airflow = U_inf.normalize();
+        let lift = forces - forces.dot(&airflow) * airflow;
+
+        // lift leans over with the wing, half sideways and half vertical
+        assert!((lift.z + lift.y).abs() < 1e-9);
+    }
+
+    #[test]
+    fn no_air_no_force() {
+        let under_test = Wing::new_area_only(1.0);
+        assert_eq!(
+            under_test.calculate_forces(&Airflow::uniform(Vector3::zeros())),
+            Vector3::zeros()
+        );
+        // falling straight down flat
+        let forces = under_test.calculate_forces(&Airflow::uniform(Vector3::new(0.0, 0.0, 10.0)));
+        assert!(forces.x.abs() < 1e-9);
+        assert!((-forces.z - 0.5 * sea_level_density() * 100.0 * 2.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn lift_tops_out_at_cl_max_then_falls_away() {
+        let under_test = Wing::new_area_only(1.0).with_stall(1.2, 0.1);
+        let stall_angle = 1.2 / under_test.dcl_dalpha();
+        assert!((under_test.lift_coefficient(stall_angle) - 1.2).abs() < 1e-9);
+        assert!((under_test.lift_coefficient(-stall_angle) + 1.2).abs() < 1e-9);
+        assert!(under_test.lift_coefficient(stall_angle + 0.05) < 1.2);
+        assert!(under_test.lift_coefficient(stall_angle + 0.2) < 1.0);
+        // flat plate lifts most at 45 degrees and none broadside
+        let flat_plate = under_test.lift_coefficient(f64::consts::FRAC_PI_4);
+        assert!((flat_plate - 1.0).abs() < 1e-9);
+        assert!(under_test.lift_coefficient(f64::consts::FRAC_PI_2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn every_direction_is_finite() {
+        let under_test = Wing::new_area_location(10.0, Vector3::new(1.0, 0.0, 0.0));
+        for degrees in -180..=180 {
+            let alpha = (degrees as f64).to_radians();
+            let velocity = Vector3::new(alpha.cos(), 0.3, alpha.sin()) * 50.0;
+            let forces = under_test.calculate_forces(&Airflow::uniform(velocity));
+            assert!(forces.iter().all(|force| force.is_finite()));
+            // drag never pushes the wing into the wind
+            assert!(forces.dot(&velocity) <= 1e-9);
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn tailslide_lift_is_small() {
+        let under_test = Wing::new_area_only(1.0);
+        let U_inf = Vector3::new(-10.0, 0.0, 0.5);
+        let forces = under_test.calculate_forces(&Airflow::uniform(U_inf));
+        assert!(forces.z.abs() < 0.5 * sea_level_density() * 100.0 * 0.2);
+    }
+
+    #[test]
+    fn induced_drag_grows_with_lift_squared() {
+        let under_test = Wing::new_area_location(10.0, Vector3::zeros()).with_drag(0.02, 0.75);
+        let alpha = 0.1;
+        let cl = under_test.lift_coefficient(alpha);
+        let expected = 0.02 + cl.powi(2) / (f64::consts::PI * 7.0 * 0.75);
+        assert!((under_test.drag_coefficient(alpha) - expected).abs() < 1e-12);
+        assert!((under_test.drag_coefficient(0.0) - 0.02).abs() < 1e-12);
+    }
+
+    #[test]
+    fn profile_drag_comes_from_the_table() {
+        let under_test = Wing::new_area_only(1.0).with_profile_drag(vec![
+            (-0.1, 0.02),
+            (0.0, 0.006),
+            (0.1, 0.016),
+        ]);
+        assert!((under_test.profile_drag_coefficient(0.05) - 0.011).abs() < 1e-12);
+        assert!((under_test.profile_drag_coefficient(-0.05) - 0.013).abs() < 1e-12);
+        assert_eq!(under_test.profile_drag_coefficient(-0.5), 0.02);
+        assert_eq!(under_test.profile_drag_coefficient(0.5), 0.016);
+    }
+
+    /// the same fin the plane has, without the rudder
+    fn fin() -> Wing {
+        Wing::new_fin(1.6, Vector3::new(-5.2, 0.0, -0.7)).with_aspect_ratio(1.6)
+    }
+
+    #[test]
+    fn sideslip_angle() {
+        let (alpha, beta) = Wing::flow_angles(&Vector3::new(30.0, 30.0, 0.0));
+        assert_eq!(alpha, 0.0);
+        assert!((beta - f64::consts::FRAC_PI_4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn fin_pushes_back_against_sideslip() {
+        let under_test = fin();
+        // moving right through the air, so the air comes from the right
+        let velocity = Vector3::new(40.0, 4.0, 0.0);
+        let forces = under_test.calculate_forces(&Airflow::uniform(velocity));
+        assert!(forces.y < -10.0);
+        assert!(forces.z.abs() < 1e-9);
+        // without sideslip it only has drag
+        let forces = under_test.calculate_forces(&Airflow::uniform(Vector3::new(40.0, 0.0, 0.0)));
+        assert!(forces.y.abs() < 1e-9);
+        assert!(forces.x < 0.0);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn spanwise_air_only_adds_skin_drag() {
+        let under_test = Wing::new_area_only(1.0);
+        let U_inf = Vector3::new(0.0, 10.0, 0.0);
+        let forces = under_test.calculate_forces(&Airflow::uniform(U_inf));
+        assert!(forces.x.abs() < 1e-12 && forces.z.abs() < 1e-12);
+        assert!((-forces.y - 0.5 * sea_level_density() * 100.0 * 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn dihedral_lifts_the_wing_into_the_sideslip() {
+        let dihedral = 5.0_f64.to_radians();
+        let mut left = Wing::new_area_location(8.0, Vector3::new(0.0, -2.5, 0.0));
+        left.transform_on_plane.rotation = UnitQuaternion::from_euler_angles(dihedral, 0.0, 0.0);
+        let mut right = Wing::new_area_location(8.0, Vector3::new(0.0, 2.5, 0.0));
+        right.transform_on_plane.rotation = UnitQuaternion::from_euler_angles(-dihedral, 0.0, 0.0);
+        // slipping to the right, the right panel meets the air at a higher alpha
+        let velocity = Vector3::new(40.0, 4.0, 2.0);
+        let left_forces = left.calculate_forces(&Airflow::uniform(velocity));
+        let right_forces = right.calculate_forces(&Airflow::uniform(velocity));
+        assert!(right_forces.z < left_forces.z);
+        // and the pair pushes back against the slip
+        assert!(left_forces.y + right_forces.y < 0.0);
+    }
 
-        println!("{:?}", forces);
+    #[test]
+    fn tail_resists_pitching() {
+        let under_test = Wing::new_area_location(5.0, Vector3::new(-5.0, 0.0, 0.0));
+        let still = under_test.calculate_forces(&Airflow::uniform(Vector3::new(40.0, 0.0, 0.0)));
+        let pitching_up = under_test.calculate_forces(&Airflow::new(
+            Vector3::new(40.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.5, 0.0),
+            Vector3::zeros(),
+        ));
+        // the tail drops as the nose comes up, so it lifts and pushes the nose back down
+        assert!(still.z.abs() < 1e-9);
+        assert!(pitching_up.z < -100.0);
+    }
 
-        assert!((forces.z - forces.y).abs() < 1e-3);
+    #[test]
+    fn wings_resist_rolling() {
+        let left = Wing::new_area_location(8.0, Vector3::new(0.0, -2.5, 0.0));
+        let right = Wing::new_area_location(8.0, Vector3::new(0.0, 2.5, 0.0));
+        let rolling_right = Airflow::new(
+            Vector3::new(40.0, 0.0, 0.0),
+            Vector3::new(0.5, 0.0, 0.0),
+            Vector3::zeros(),
+        );
+        // the falling right wing meets the air at a higher alpha
+        assert!(right.calculate_forces(&rolling_right).z < -50.0);
+        assert!(left.calculate_forces(&rolling_right).z > 50.0);
+    }
+
+    fn lifting_at(location: Vector3<f64>) -> Vector3<f64> {
+        let under_test = Wing::new_area_location(5.0, location);
+        let airflow = Airflow::new(
+            Vector3::new(40.0, 0.0, 2.0),
+            Vector3::zeros(),
+            Vector3::new(0.5, 0.0, 0.1),
+        );
+        let (forces, moments) = under_test.loads(&airflow);
+        let arm = location - Vector3::new(0.5, 0.0, 0.1);
+        assert!((moments - arm.cross(&forces)).norm() < 1e-9);
+        moments
+    }
+
+    #[test]
+    fn lift_ahead_of_the_cg_pitches_up() {
+        assert!(lifting_at(Vector3::new(1.5, 0.0, 0.1)).y > 50.0);
+        assert!(lifting_at(Vector3::new(-4.5, 0.0, 0.1)).y < -500.0);
+    }
+
+    #[test]
+    fn lift_on_the_right_rolls_left() {
+        let moments = lifting_at(Vector3::new(0.5, 3.0, 0.1));
+        assert!(moments.x < -500.0);
+        assert!(moments.y.abs() < 1e-9);
+    }
+
+    #[test]
+    fn camber_pitches_nose_down() {
+        let under_test =
+            Wing::new_area_location(10.0, Vector3::zeros()).with_pitching_moment(-0.05);
+        let airflow = Airflow::uniform(Vector3::new(40.0, 0.0, 0.0));
+        let expected = 0.5 * sea_level_density() * 1600.0 * 10.0 * under_test.mean_chord() * -0.05;
+        let moments = under_test.calculate_moments(&airflow);
+        assert!((moments.y - expected).abs() < 1e-9);
+        assert!(moments.x.abs() < 1e-12 && moments.z.abs() < 1e-12);
+    }
+
+    #[test]
+    fn stalled_wing_pushes_from_further_back() {
+        let under_test = Wing::new_area_location(10.0, Vector3::zeros());
+        let airflow = Airflow::uniform(Vector3::new(20.0, 0.0, 20.0));
+        // lift and drag both push up and back from behind the aerodynamic centre, nose down
+        assert!(under_test.calculate_moments(&airflow).y < 0.0);
+    }
+
+    #[test]
+    fn elevator_changes_lift_and_pitch() {
+        let mut under_test = Wing::new_area_location(5.0, Vector3::new(-5.0, 0.0, 0.0))
+            .with_control_surface(ControlSurface::new(0.4, 0.26, 0.44, Mixing::elevator()));
+        let airflow = Airflow::uniform(Vector3::new(40.0, 0.0, 0.0));
+        assert!(under_test.calculate_forces(&airflow).z.abs() < 1e-9);
+        let mut controls = Cockpit::new();
+        controls.elevator = 0.5;
+        under_test.update_controls(&controls);
+        // pulling back pushes the tail down and the nose up
+        let (forces, moments) = under_test.loads(&airflow);
+        assert!(forces.z > 100.0);
+        assert!(moments.y > 500.0);
+        // and costs some drag
+        assert!(forces.x < -0.5 * sea_level_density() * 1600.0 * 5.0 * 0.01);
+    }
+
+    #[test]
+    fn half_wing_keeps_the_whole_chord() {
+        let whole = Wing::new_area_location(16.0, Vector3::zeros()).with_aspect_ratio(4.0);
+        let half = Wing::new_area_location(8.0, Vector3::zeros())
+            .with_aspect_ratio(4.0)
+            .as_half();
+        assert_eq!(whole.mean_chord(), 2.0);
+        assert_eq!(half.mean_chord(), 2.0);
+        // and the single strip a wing starts as keeps up
+        assert_eq!(
+            half.strips(),
+            &[Strip {
+                offset: Vector3::zeros(),
+                chord: 2.0,
+                area: 8.0,
+                twist: 0.0,
+                roll: 0.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn both_constructors_agree_on_the_shape() {
+        let only = Wing::new_area_only(10.0);
+        let located = Wing::new_area_location(10.0, Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(only.mean_chord(), located.mean_chord());
+        assert_eq!(only.span(), located.span());
+    }
+
+    #[test]
+    fn drawn_out_wing_takes_its_size_from_the_planform() {
+        let planform = Planform::new(Root::LeftEnd, 5.0, 2.0, 1.0);
+        let under_test = Wing::from_planform(planform, Vector3::new(0.3, 0.6, 0.0), 4);
+        assert_eq!(under_test.planform(), Some(&planform));
+        assert!((under_test.span() - planform.span).abs() < 1e-12);
+        assert!((under_test.mean_chord() - 1.5).abs() < 1e-12);
+        // the middle of the panel is halfway out from the root
+        let strips = under_test.strips();
+        let middle = strips.iter().map(|strip| strip.offset.y).sum::<f64>() / 4.0;
+        assert!((middle - 2.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn planform_dihedral_rolls_away_from_a_slip() {
+        let rolling_moment = |dihedral: f64| {
+            let planform =
+                Planform::new(Root::Centre, 10.0, 1.6, 1.6).with_dihedral(dihedral.to_radians());
+            let under_test = Wing::from_planform(planform, Vector3::zeros(), 4);
+            // slipping to the right, the air comes from the right
+            let airflow = Airflow::uniform(Vector3::new(40.0, 4.0, 2.0));
+            under_test.calculate_moments(&airflow).x
+        };
+        assert!(rolling_moment(0.0).abs() < 1.0);
+        assert!(rolling_moment(5.0) < -100.0);
+    }
+
+    const POLARS: &str = "re,alpha,cl,cd,cm
+1e6,-10,-0.9,0.012,-0.05
+1e6,0,0.2,0.006,-0.05
+1e6,10,1.3,0.012,-0.05
+1e6,16,1.5,0.03,-0.04
+";
+
+    #[test]
+    fn lift_and_drag_from_the_polar() {
+        let airfoil = Arc::new(Airfoil::from_csv(POLARS).unwrap());
+        let under_test = Wing::new_area_location(10.0, Vector3::zeros())
+            .with_aspect_ratio(1e9)
+            .with_airfoil(airfoil);
+        let airflow = Airflow::uniform(Vector3::new(40.0, 0.0, 0.0));
+        let (forces, moments) = under_test.loads(&airflow);
+        let dynamic_pressure = 0.5 * sea_level_density() * 1600.0;
+        // cambered, so it lifts at zero alpha and pitches nose down
+        let lift = dynamic_pressure * 10.0 * 0.2 * prandtl_glauert(&under_test, 40.0);
+        assert!((-forces.z - lift).abs() < 1e-3);
+        assert!((-forces.x - dynamic_pressure * 10.0 * 0.006).abs() < 1e-3);
+        assert!(moments.y < 0.0);
+    }
+
+    #[test]
+    fn stalls_past_the_end_of_the_polar() {
+        let airfoil = Arc::new(Airfoil::from_csv(POLARS).unwrap());
+        let under_test = Wing::new_area_location(10.0, Vector3::zeros()).with_airfoil(airfoil);
+        let beyond = under_test.coefficients(1.2, 1e6, 0.0);
+        assert_eq!(beyond.stalled, 1.0);
+        assert!((beyond.lift - 2.4_f64.sin()).abs() < 1e-12);
+        let inside = under_test.coefficients(0.1, 1e6, 0.0);
+        assert_eq!(inside.stalled, 0.0);
+        // the finite wing needs more alpha than the section for the same lift
+        assert!(inside.lift < 0.2 + 1.1 * 0.1_f64.to_degrees() / 10.0);
+    }
+
+    #[test]
+    fn tapered_strips_add_up() {
+        let strips = Strip::tapered(16.0, 10.0, 0.5, 0.05, 10, Root::Centre);
+        let area: f64 = strips.iter().map(|strip| strip.area).sum();
+        assert!((area - 16.0).abs() < 1e-12);
+        // tips are narrower and twisted down
+        assert!(strips[0].chord < strips[4].chord);
+        assert!(strips[0].twist < strips[4].twist);
+        assert_eq!(strips[0].chord, strips[9].chord);
+        let half = Strip::tapered(8.0, 5.0, 0.5, 0.0, 5, Root::RightEnd);
+        assert!(half[0].chord < half[4].chord);
+    }
+
+    #[test]
+    fn one_even_strip_is_the_plain_wing() {
+        let plain = Wing::new_area_location(10.0, Vector3::new(1.0, 0.0, 0.0));
+        let stripped = Wing::new_area_location(10.0, Vector3::new(1.0, 0.0, 0.0)).with_strips(
+            1,
+            1.0,
+            0.0,
+            Root::Centre,
+        );
+        let airflow = Airflow::new(
+            Vector3::new(40.0, 1.0, 3.0),
+            Vector3::zeros(),
+            Vector3::new(0.2, 0.0, 0.1),
+        );
+        let (plain_forces, plain_moments) = plain.loads(&airflow);
+        let (stripped_forces, stripped_moments) = stripped.loads(&airflow);
+        assert!((plain_forces - stripped_forces).norm() < 1e-9);
+        assert!((plain_moments - stripped_moments).norm() < 1e-9);
+    }
+
+    fn rolling_right(alpha: f64) -> Airflow {
+        Airflow::new(
+            Vector3::new(alpha.cos(), 0.0, alpha.sin()) * 40.0,
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::zeros(),
+        )
+    }
+
+    #[test]
+    fn rolling_gives_adverse_yaw() {
+        let under_test =
+            Wing::new_area_location(16.0, Vector3::zeros()).with_strips(10, 0.6, 0.0, Root::Centre);
+        let moments = under_test.calculate_moments(&rolling_right(0.1));
+        // rolls back against the roll and yaws the nose away from it
+        assert!(moments.x < 0.0);
+        assert!(moments.z < 0.0);
+    }
+
+    #[test]
+    fn downgoing_tip_stalls_first() {
+        let under_test =
+            Wing::new_area_location(16.0, Vector3::zeros()).with_strips(10, 0.6, 0.0, Root::Centre);
+        let stalled = under_test.stalled_fractions(&rolling_right(0.2));
+        assert!(stalled[9] > 0.0);
+        assert_eq!(stalled[0], 0.0);
+        assert!(stalled.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn ground_effect_fades_with_height() {
+        let under_test = Wing::new_area_location(16.0, Vector3::zeros());
+        let (lift, induced) = under_test.ground_effect(1.0, Vector3::y());
+        assert!(lift > 1.05);
+        assert!(induced < 0.7);
+        let (lift, induced) = under_test.ground_effect(100.0, Vector3::y());
+        assert!((lift - 1.0).abs() < 1e-3);
+        assert!((induced - 1.0).abs() < 1e-3);
+        // standing on its end there's no wing near the ground to speak of
+        assert_eq!(under_test.ground_effect(1.0, Vector3::z()), (1.0, 1.0));
+    }
+
+    #[test]
+    fn floats_near_the_ground() {
+        let under_test = Wing::new_area_location(16.0, Vector3::zeros());
+        let velocity = Vector3::new(30.0, 0.0, 2.0);
+        let high = Airflow::uniform(velocity);
+        let low = Airflow::uniform(velocity).with_ground(1.0, UnitQuaternion::identity());
+        let lift_to_drag = |airflow: &Airflow| {
+            let forces = under_test.calculate_forces(airflow);
+            forces.z / forces.x
+        };
+        assert!(under_test.calculate_forces(&low).z < under_test.calculate_forces(&high).z);
+        assert!(lift_to_drag(&low) > lift_to_drag(&high));
+    }
+
+    #[test]
+    fn drag_climbs_past_critical_mach() {
+        let under_test = Wing::new_area_location(16.0, Vector3::zeros()).with_critical_mach(0.7);
+        let (lift, drag_rise, tuck) = under_test.compressibility(0.5);
+        assert!((lift - 1.0 / 0.75_f64.sqrt()).abs() < 1e-12);
+        assert_eq!((drag_rise, tuck), (0.0, 0.0));
+        let (_, drag_rise, tuck) = under_test.compressibility(0.85);
+        assert!(drag_rise > 0.005);
+        assert_eq!(tuck, 1.0);
+        // nothing blows up going supersonic
+        let (lift, drag_rise, _) = under_test.compressibility(1.5);
+        assert!(lift.is_finite() && drag_rise < 0.2);
+    }
+
+    #[test]
+    fn mach_tuck_pitches_the_nose_down() {
+        let under_test = Wing::new_area_location(16.0, Vector3::zeros());
+        let pitch_per_lift = |speed: f64| {
+            let airflow = Airflow::uniform(Vector3::new(speed, 0.0, 0.02 * speed));
+            let (forces, moments) = under_test.loads(&airflow);
+            moments.y / -forces.z
+        };
+        assert!(pitch_per_lift(100.0).abs() < 1e-9);
+        assert!(pitch_per_lift(280.0) < -0.2);
+    }
+
+    #[test]
+    fn thin_air_lifts_less() {
+        let under_test = Wing::new_area_location(16.0, Vector3::zeros());
+        let velocity = Vector3::new(60.0, 0.0, 3.0);
+        let low = under_test.calculate_forces(&Airflow::uniform(velocity));
+        let high = under_test.calculate_forces(
+            &Airflow::uniform(velocity).with_atmosphere(Atmosphere::standard(5000.0)),
+        );
+        // about 60 % of the sea level density at 5000 m
+        let ratio = high.z / low.z;
+        assert!(ratio > 0.55 && ratio < 0.65, "{ratio}");
+    }
+
+    #[test]
+    fn flaps_lift_more_and_stall_sooner() {
+        let stages = vec![0.0, 30.0_f64.to_radians()];
+        let mut under_test = Wing::new_area_location(16.0, Vector3::zeros()).with_flap(Flap::new(
+            FlapKind::Plain,
+            0.25,
+            1.0,
+            stages,
+            1.0,
+        ));
+        let lift_at = |wing: &Wing, alpha: f64| {
+            let airflow = Airflow::uniform(Vector3::new(alpha.cos(), 0.0, alpha.sin()) * 30.0);
+            -wing.calculate_forces(&airflow).z
+        };
+        let stall = |wing: &Wing| {
+            (0..300)
+                .map(|i| i as f64 * 0.001)
+                .max_by(|&a, &b| lift_at(wing, a).total_cmp(&lift_at(wing, b)))
+                .unwrap()
+        };
+        let clean_lift = lift_at(&under_test, 0.05);
+        let clean_stall = stall(&under_test);
+        let mut controls = Cockpit::new();
+        controls.flaps = 1;
+        under_test.update_controls(&controls);
+        under_test.move_actuators(5.0);
+        assert!(lift_at(&under_test, 0.05) > clean_lift);
+        assert!(lift_at(&under_test, stall(&under_test)) > lift_at(&under_test, clean_stall));
+        assert!(stall(&under_test) < clean_stall);
+    }
+
+    #[test]
+    fn spoilers_dump_lift() {
+        let mut under_test = Wing::new_area_location(16.0, Vector3::zeros())
+            .with_spoiler(Spoiler::new(1.0))
+            .with_slat(Slat::new(0.1, 1.0));
+        let airflow = Airflow::uniform(Vector3::new(40.0, 0.0, 2.0));
+        let clean = under_test.calculate_forces(&airflow);
+        let mut controls = Cockpit::new();
+        controls.spoilers = 1.0;
+        under_test.update_controls(&controls);
+        under_test.move_actuators(1.0);
+        let spoiled = under_test.calculate_forces(&airflow);
+        assert!(spoiled.z > 0.5 * clean.z);
+        assert!(spoiled.x < clean.x);
     }
 }
@@ -2,7 +2,18 @@ use std::f32;
 
 use bevy::{DefaultPlugins, prelude::*};
 
-use flight_dynamics_lib::{cockpit::Cockpit, plane::Plane};
+use flight_dynamics_lib::{
+    cockpit::Cockpit,
+    damage::SimEvent,
+    plane::Plane,
+    timestep::{self, FixedTimestep},
+};
+use nalgebra::Isometry3;
+
+/// physics runs at this rate no matter what the display is doing
+const PHYSICS_RATE_HZ: f64 = 240.0;
+/// most physics steps in one frame, anything more than this is dropped so a hitch can't snowball
+const MAX_SUBSTEPS: usize = 16;
 
 fn main() {
     App::new()
@@ -31,17 +42,45 @@ impl CockpitConnector {
 #[derive(Resource)]
 struct PlaneConnector {
     plane: flight_dynamics_lib::plane::Plane,
+    timestep: FixedTimestep,
+    /// where the plane was one physics step ago, for interpolating the drawn position
+    previous_transform: Isometry3<f64>,
+    /// the latest damage, shown on the overlay
+    last_event: Option<SimEvent>,
 }
 
 impl PlaneConnector {
     fn new() -> PlaneConnector {
+        let plane = Plane::new_in_flight();
         PlaneConnector {
-            plane: Plane::new_in_flight(),
+            previous_transform: plane.transform_in_world,
+            plane,
+            timestep: FixedTimestep::new(PHYSICS_RATE_HZ, MAX_SUBSTEPS),
+            last_event: None,
         }
     }
 
-    fn run(&mut self, time: f64, controls: &Cockpit) {
-        self.plane.run_physics(time, controls);
+    /// run however many fixed physics steps fit into this frame, the flight is over once the plane is destroyed
+    fn run(&mut self, frame_time: f64, controls: &Cockpit) {
+        let steps = self.timestep.advance(frame_time);
+        for _ in 0..steps {
+            if self.plane.is_destroyed() {
+                break;
+            }
+            self.previous_transform = self.plane.transform_in_world;
+            self.plane.run_physics(self.timestep.step, controls);
+        }
+        if let Some(event) = self.plane.drain_events().pop() {
+            self.last_event = Some(event);
+        }
+    }
+
+    fn render_transform(&self) -> Isometry3<f64> {
+        timestep::interpolate(
+            &self.previous_transform,
+            &self.plane.transform_in_world,
+            self.timestep.alpha(),
+        )
     }
 }
 
@@ -138,35 +177,52 @@ fn update_state(
     controls.controls.throttle = controls.controls.throttle.min(1.0).max(0.0);
 
     if keyboard_input.pressed(KeyCode::KeyA) {
-        controls.controls.yaw = 0.01;
+        controls.controls.yaw = 1.0;
     }
 
     if keyboard_input.pressed(KeyCode::KeyD) {
-        controls.controls.yaw = -0.01;
+        controls.controls.yaw = -1.0;
     }
 
     if keyboard_input.pressed(KeyCode::KeyW) {
-        controls.controls.elevator = -0.01;
+        controls.controls.elevator = -1.0;
     }
 
     if keyboard_input.pressed(KeyCode::KeyS) {
-        controls.controls.elevator = 0.01;
+        controls.controls.elevator = 1.0;
     }
 
     if keyboard_input.pressed(KeyCode::KeyQ) {
-        controls.controls.roll = 0.01;
+        controls.controls.roll = 1.0;
     }
 
     if keyboard_input.pressed(KeyCode::KeyE) {
-        controls.controls.roll = -0.01;
+        controls.controls.roll = -1.0;
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyB) {
+        controls.controls.brake_left = 1.0;
+        controls.controls.brake_right = 1.0;
+    }
+
+    // flap lever clicks a stage at a time, the 172 has three
+    if keyboard_input.just_pressed(KeyCode::KeyF) {
+        controls.controls.flaps = (controls.controls.flaps + 1).min(3);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        controls.controls.flaps = controls.controls.flaps.saturating_sub(1);
     }
 
+    plane.run(time.delta_secs_f64(), &controls.controls);
+
+    let render_transform = plane.render_transform();
     for (_, mut transform) in camera {
-        transform.translation.x = plane.plane.position.x as f32;
-        transform.translation.y = 2.0 - 1.0 * plane.plane.position.z as f32;
-        transform.translation.z = -1.0 * plane.plane.position.y as f32;
+        transform.translation.x = render_transform.translation.x as f32;
+        transform.translation.y = 2.0 - 1.0 * render_transform.translation.z as f32;
+        transform.translation.z = -1.0 * render_transform.translation.y as f32;
 
-        let (roll, pitch, yaw) = plane.plane.pointing.euler_angles();
+        let (roll, pitch, yaw) = render_transform.rotation.euler_angles();
 
         transform.rotation = Quat::from_euler(
             EulerRot::YXZ,
@@ -175,22 +231,24 @@ fn update_state(
             roll as f32,
         );
     }
-
-    plane.run(time.delta_secs_f64(), &controls.controls);
 }
 
 fn overlay(text: Query<&mut Text>, plane: ResMut<PlaneConnector>) {
+    let state = plane.plane.state();
+    let damage = match plane.last_event {
+        Some(event) => format!("{:.1}s {:?} {:?}", event.time, event.severity, event.kind),
+        None => "none".to_string(),
+    };
     for mut words in text {
-        let (_, ppitch, _) = plane.plane.pointing.euler_angles();
         **words = format!(
-            "pitch angle deg {:.2},\nvelocity{:?}\naltitude {:.1}\nclimb rate {:.1}",
-            ppitch * -180.0 / std::f64::consts::PI,
-            plane.plane.velocity,
-            -plane.plane.position.z,
-            -plane.plane.velocity.z
+            "pitch angle deg {:.2},\nairspeed {:.1}\naltitude {:.1}\nclimb rate {:.1}\nalpha deg {:.1}\nload factor {:.2}\ndamage {}",
+            state.pitch.to_degrees(),
+            state.airspeed,
+            state.height_above_ground,
+            -state.velocity_ned.z,
+            state.alpha.to_degrees(),
+            state.load_factor,
+            damage,
         );
-        println!("p{:?}", plane.plane.position);
-        println!("v{:?}", plane.plane.velocity);
-        println!();
     }
 }